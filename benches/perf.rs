@@ -12,7 +12,7 @@ use std::path::Path;
 use std::sync::Arc;
 
 use arrow::array;
-use arrow::datatypes::{DataType, Field, Int64Type, Schema};
+use arrow::datatypes::{DataType, Field, Int32Type, Int64Type, Schema};
 use arrow::record_batch::RecordBatch;
 use criterion::{Criterion, criterion_group, criterion_main};
 use rand::{Rng, SeedableRng};
@@ -75,6 +75,70 @@ fn setup_batch_nullable(params: &Params) -> RecordBatch {
     .unwrap()
 }
 
+fn setup_batch_bool(params: &Params) -> RecordBatch {
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(123_456);
+
+    let mut columns: Vec<Arc<dyn array::Array>> = Vec::new();
+
+    for _ in 0..params.num_columns {
+        let mut b = array::BooleanBuilder::with_capacity(params.num_records);
+
+        for _ in 0..params.num_records {
+            if rng.random_bool(0.1) {
+                b.append_null()
+            } else {
+                b.append_value(rng.random())
+            }
+        }
+
+        columns.push(Arc::new(b.finish()));
+    }
+
+    RecordBatch::try_new(
+        Arc::new(Schema::new(
+            (0..params.num_columns)
+                .map(|i| Field::new(format!("col_{i}"), DataType::Boolean, true))
+                .collect::<Vec<_>>(),
+        )),
+        columns,
+    )
+    .unwrap()
+}
+
+fn setup_batch_run_end_encoded(params: &Params) -> RecordBatch {
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(123_456);
+
+    let mut columns: Vec<Arc<dyn array::Array>> = Vec::new();
+
+    for _ in 0..params.num_columns {
+        let mut b = array::PrimitiveRunBuilder::<Int32Type, Int64Type>::new();
+
+        let mut remaining = params.num_records;
+        while remaining != 0 {
+            let run_length = rng.random_range(1..=100).min(remaining);
+            let value: i64 = rng.random();
+            for _ in 0..run_length {
+                b.append_value(value);
+            }
+            remaining -= run_length;
+        }
+
+        columns.push(Arc::new(b.finish()));
+    }
+
+    RecordBatch::try_new(
+        Arc::new(Schema::new(
+            columns
+                .iter()
+                .enumerate()
+                .map(|(i, c)| Field::new(format!("col_{i}"), c.data_type().clone(), true))
+                .collect::<Vec<_>>(),
+        )),
+        columns,
+    )
+    .unwrap()
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 
 fn setup_flat_data(params: &Params) -> Vec<u8> {
@@ -177,6 +241,8 @@ fn bench(c: &mut Criterion) {
 
     let batch = setup_batch(&params);
     let batch_null = setup_batch_nullable(&params);
+    let batch_bool = setup_batch_bool(&params);
+    let batch_ree = setup_batch_run_end_encoded(&params);
     let flat_data = setup_flat_data(&params);
 
     {
@@ -219,6 +285,45 @@ fn bench(c: &mut Criterion) {
         group.bench_function("sha3_256_i64_with_nulls", |b| {
             b.iter(|| arrow_digest::RecordDigestV0::<sha3::Sha3_256>::digest(&batch_null));
         });
+
+        group.bench_function("sha3_256_i64_run_end_encoded", |b| {
+            b.iter(|| arrow_digest::RecordDigestV0::<sha3::Sha3_256>::digest(&batch_ree));
+        });
+
+        group.bench_function("sha3_256_bool_v0", |b| {
+            b.iter(|| arrow_digest::RecordDigestV0::<sha3::Sha3_256>::digest(&batch_bool));
+        });
+
+        group.bench_function("sha3_256_bool_v1", |b| {
+            b.iter(|| arrow_digest::RecordDigestV1::<sha3::Sha3_256>::digest(&batch_bool));
+        });
+
+        group.bench_function("sha3_256_i64_no_nulls_v1", |b| {
+            b.iter(|| arrow_digest::RecordDigestV1::<sha3::Sha3_256>::digest(&batch));
+        });
+
+        #[cfg(feature = "xxh3")]
+        group.bench_function("xxh3_128_i64_no_nulls_v1", |b| {
+            b.iter(|| arrow_digest::RecordDigestV1::<arrow_digest::Xxh3_128>::digest(&batch));
+        });
+
+        group.bench_function("sha3_256_i64_with_nulls_v1", |b| {
+            b.iter(|| arrow_digest::RecordDigestV1::<sha3::Sha3_256>::digest(&batch_null));
+        });
+
+        #[cfg(feature = "xxh3")]
+        group.bench_function("xxh3_128_i64_with_nulls_v1", |b| {
+            b.iter(|| arrow_digest::RecordDigestV1::<arrow_digest::Xxh3_128>::digest(&batch_null));
+        });
+
+        #[cfg(feature = "std-hasher")]
+        group.bench_function("std_hasher_i64_with_nulls_v1", |b| {
+            b.iter(|| {
+                arrow_digest::RecordDigestV1::<
+                    arrow_digest::StdHasher<std::hash::DefaultHasher>,
+                >::digest(&batch_null)
+            });
+        });
     }
 }
 
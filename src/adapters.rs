@@ -0,0 +1,627 @@
+use digest::{
+    DynDigest, ExtendableOutput, InvalidBufferSize, InvalidOutputSize, Update, VariableOutput,
+};
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+// Digests of nested arrays are never shorter than this, so that short final digests don't
+// make it easier to find collisions of nested digests
+const MIN_NESTED_OUTPUT_SIZE: usize = 32;
+
+/// Adapts an extendable-output function (e.g. SHAKE256 or BLAKE3) to [`DynDigest`] with the
+/// output size chosen by the caller, so it can be used with `Dyn*` digests like
+/// [`crate::DynRecordDigestV1`].
+///
+/// Output size of nested digests is at least 32 bytes (see
+/// [nested digests](crate#nested-digests)), so digests up to 32 bytes long are prefixes of
+/// one another, while longer digests are unrelated.
+#[derive(Clone)]
+pub struct Xof<X> {
+    hasher: X,
+    output_size: usize,
+}
+
+impl<X: ExtendableOutput + Default> Xof<X> {
+    pub fn new(output_size: usize) -> Self {
+        Self {
+            hasher: X::default(),
+            output_size,
+        }
+    }
+}
+
+impl<X: ExtendableOutput + Default + Clone + 'static> DynDigest for Xof<X> {
+    fn update(&mut self, data: &[u8]) {
+        Update::update(&mut self.hasher, data);
+    }
+
+    fn finalize_into(mut self, buf: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        self.finalize_into_reset(buf)
+    }
+
+    fn finalize_into_reset(&mut self, out: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        if out.len() != self.output_size {
+            return Err(InvalidBufferSize);
+        }
+        std::mem::take(&mut self.hasher).finalize_xof_into(out);
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.hasher = X::default();
+    }
+
+    fn output_size(&self) -> usize {
+        self.output_size
+    }
+
+    fn box_clone(&self) -> Box<dyn DynDigest> {
+        Box::new(Self {
+            hasher: self.hasher.clone(),
+            output_size: self.output_size.max(MIN_NESTED_OUTPUT_SIZE),
+        })
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+/// Adapts a hasher with the output size selected at runtime (e.g. `Blake2bVar`) to
+/// [`DynDigest`], so it can be used with `Dyn*` digests like [`crate::DynRecordDigestV1`].
+///
+/// Output size of nested digests is at least 32 bytes when supported by the hasher (see
+/// [nested digests](crate#nested-digests)).
+#[derive(Clone)]
+pub struct VarOutput<V> {
+    hasher: V,
+    // Hasher in its initial state, as variable-output hashers can't always be reset
+    initial: V,
+}
+
+impl<V: VariableOutput + Clone> VarOutput<V> {
+    pub fn new(output_size: usize) -> Result<Self, InvalidOutputSize> {
+        let hasher = V::new(output_size)?;
+        Ok(Self {
+            initial: hasher.clone(),
+            hasher,
+        })
+    }
+}
+
+impl<V: VariableOutput + Clone + 'static> DynDigest for VarOutput<V> {
+    fn update(&mut self, data: &[u8]) {
+        Update::update(&mut self.hasher, data);
+    }
+
+    fn finalize_into(self, buf: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        self.hasher.finalize_variable(buf)
+    }
+
+    fn finalize_into_reset(&mut self, out: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        std::mem::replace(&mut self.hasher, self.initial.clone()).finalize_variable(out)
+    }
+
+    fn reset(&mut self) {
+        self.hasher = self.initial.clone();
+    }
+
+    fn output_size(&self) -> usize {
+        self.hasher.output_size()
+    }
+
+    // State of the clone is reset, as a hasher of a different output size has to be created
+    fn box_clone(&self) -> Box<dyn DynDigest> {
+        if self.output_size() < MIN_NESTED_OUTPUT_SIZE
+            && let Ok(nested) = Self::new(MIN_NESTED_OUTPUT_SIZE.min(V::MAX_OUTPUT_SIZE))
+        {
+            return Box::new(nested);
+        }
+        Box::new(self.clone())
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+/// Non-cryptographic [XXH3](https://xxhash.com) hash with 64-bit output that can be used with
+/// generic digests, e.g. `RecordDigestV1::<Xxh3_64>` (see
+/// [non-cryptographic hashes](crate#non-cryptographic-hashes)).
+#[cfg(feature = "xxh3")]
+#[derive(Clone, Default)]
+pub struct Xxh3_64(xxhash_rust::xxh3::Xxh3);
+
+#[cfg(feature = "xxh3")]
+impl digest::HashMarker for Xxh3_64 {}
+
+#[cfg(feature = "xxh3")]
+impl digest::OutputSizeUser for Xxh3_64 {
+    type OutputSize = digest::consts::U8;
+}
+
+#[cfg(feature = "xxh3")]
+impl Update for Xxh3_64 {
+    #[inline]
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+}
+
+#[cfg(feature = "xxh3")]
+impl digest::FixedOutput for Xxh3_64 {
+    fn finalize_into(self, out: &mut digest::Output<Self>) {
+        out.copy_from_slice(&self.0.digest().to_le_bytes());
+    }
+}
+
+/// Non-cryptographic [XXH3](https://xxhash.com) hash with 128-bit output that can be used
+/// with generic digests, e.g. `RecordDigestV1::<Xxh3_128>` (see
+/// [non-cryptographic hashes](crate#non-cryptographic-hashes)).
+#[cfg(feature = "xxh3")]
+#[derive(Clone, Default)]
+pub struct Xxh3_128(xxhash_rust::xxh3::Xxh3);
+
+#[cfg(feature = "xxh3")]
+impl digest::HashMarker for Xxh3_128 {}
+
+#[cfg(feature = "xxh3")]
+impl digest::OutputSizeUser for Xxh3_128 {
+    type OutputSize = digest::consts::U16;
+}
+
+#[cfg(feature = "xxh3")]
+impl Update for Xxh3_128 {
+    #[inline]
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+}
+
+#[cfg(feature = "xxh3")]
+impl digest::FixedOutput for Xxh3_128 {
+    fn finalize_into(self, out: &mut digest::Output<Self>) {
+        out.copy_from_slice(&self.0.digest128().to_le_bytes());
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+/// [BLAKE3](https://github.com/BLAKE3-team/BLAKE3) hash with 256-bit output that can be used
+/// with generic digests, e.g. `RecordDigestV1::<Blake3>`, and as an extendable-output function
+/// via [`Xof`]. Implements traits of `digest` 0.10 on top of `blake3::Hasher`, so it doesn't
+/// depend on the version of `digest` that the `blake3` crate implements its own traits for.
+#[cfg(feature = "blake3")]
+#[derive(Clone, Default)]
+pub struct Blake3(blake3::Hasher);
+
+#[cfg(feature = "blake3")]
+impl digest::HashMarker for Blake3 {}
+
+#[cfg(feature = "blake3")]
+impl digest::OutputSizeUser for Blake3 {
+    type OutputSize = digest::consts::U32;
+}
+
+#[cfg(feature = "blake3")]
+impl Update for Blake3 {
+    #[inline]
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl digest::FixedOutput for Blake3 {
+    fn finalize_into(self, out: &mut digest::Output<Self>) {
+        out.copy_from_slice(self.0.finalize().as_bytes());
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl digest::Reset for Blake3 {
+    fn reset(&mut self) {
+        self.0.reset();
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl digest::FixedOutputReset for Blake3 {
+    fn finalize_into_reset(&mut self, out: &mut digest::Output<Self>) {
+        out.copy_from_slice(self.0.finalize().as_bytes());
+        self.0.reset();
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl ExtendableOutput for Blake3 {
+    type Reader = Blake3Reader;
+
+    fn finalize_xof(self) -> Self::Reader {
+        Blake3Reader(self.0.finalize_xof())
+    }
+}
+
+/// Reader of the extendable output of [`Blake3`]
+#[cfg(feature = "blake3")]
+pub struct Blake3Reader(blake3::OutputReader);
+
+#[cfg(feature = "blake3")]
+impl digest::XofReader for Blake3Reader {
+    fn read(&mut self, buffer: &mut [u8]) {
+        self.0.fill(buffer);
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+// Feeds bytes into a `std::hash::Hasher` in blocks of a fixed size. Unlike digests, many
+// hashers produce different results depending on how the input was split between `write()`
+// calls, so buffering keeps the result independent of how the data was split into batches.
+#[cfg(feature = "std-hasher")]
+#[derive(Clone, Default)]
+struct BlockWriter<H> {
+    hasher: H,
+    block: Vec<u8>,
+}
+
+#[cfg(feature = "std-hasher")]
+impl<H: std::hash::Hasher> BlockWriter<H> {
+    const BLOCK_SIZE: usize = 8 * 1024;
+
+    fn new(hasher: H) -> Self {
+        Self {
+            hasher,
+            block: Vec::new(),
+        }
+    }
+
+    fn write(&mut self, mut data: &[u8]) {
+        if !self.block.is_empty() {
+            let len = data.len().min(Self::BLOCK_SIZE - self.block.len());
+            self.block.extend_from_slice(&data[..len]);
+            data = &data[len..];
+            if self.block.len() < Self::BLOCK_SIZE {
+                return;
+            }
+            self.hasher.write(&self.block);
+            self.block.clear();
+        }
+
+        // Full blocks are written directly without copying
+        let mut blocks = data.chunks_exact(Self::BLOCK_SIZE);
+        for block in &mut blocks {
+            self.hasher.write(block);
+        }
+        self.block.extend_from_slice(blocks.remainder());
+    }
+
+    fn finish(mut self) -> [u8; 8] {
+        if !self.block.is_empty() {
+            self.hasher.write(&self.block);
+        }
+        self.hasher.finish().to_le_bytes()
+    }
+}
+
+/// Adapts a [`std::hash::Hasher`] with a deterministic default state (e.g. `FxHasher`) to be
+/// used with generic digests, e.g. `RecordDigestV1::<StdHasher<FxHasher>>`, producing 64-bit
+/// digests. Hashers like `std::hash::DefaultHasher` don't guarantee stability across Rust
+/// releases, so such digests should only be used within a process.
+#[cfg(feature = "std-hasher")]
+#[derive(Clone, Default)]
+pub struct StdHasher<H>(BlockWriter<H>);
+
+#[cfg(feature = "std-hasher")]
+impl<H> digest::HashMarker for StdHasher<H> {}
+
+#[cfg(feature = "std-hasher")]
+impl<H> digest::OutputSizeUser for StdHasher<H> {
+    type OutputSize = digest::consts::U8;
+}
+
+#[cfg(feature = "std-hasher")]
+impl<H: std::hash::Hasher> Update for StdHasher<H> {
+    #[inline]
+    fn update(&mut self, data: &[u8]) {
+        self.0.write(data);
+    }
+}
+
+#[cfg(feature = "std-hasher")]
+impl<H: std::hash::Hasher> digest::FixedOutput for StdHasher<H> {
+    fn finalize_into(self, out: &mut digest::Output<Self>) {
+        out.copy_from_slice(&self.0.finish());
+    }
+}
+
+/// Adapts a [`std::hash::BuildHasher`] (e.g. a seeded one) to [`DynDigest`] producing 64-bit
+/// digests, so it can be used with `Dyn*` digests like [`crate::DynRecordDigestV1`]. Every
+/// nested digest uses a hasher built by the same builder, so builders with random state like
+/// `std::hash::RandomState` produce digests that are only comparable when computed with
+/// clones of the same builder.
+#[cfg(feature = "std-hasher")]
+#[derive(Clone)]
+pub struct StdBuildHasher<B: std::hash::BuildHasher> {
+    build_hasher: B,
+    writer: BlockWriter<B::Hasher>,
+}
+
+#[cfg(feature = "std-hasher")]
+impl<B: std::hash::BuildHasher> StdBuildHasher<B> {
+    pub fn new(build_hasher: B) -> Self {
+        Self {
+            writer: BlockWriter::new(build_hasher.build_hasher()),
+            build_hasher,
+        }
+    }
+}
+
+#[cfg(feature = "std-hasher")]
+impl<B> DynDigest for StdBuildHasher<B>
+where
+    B: std::hash::BuildHasher + Clone + 'static,
+    B::Hasher: Clone,
+{
+    fn update(&mut self, data: &[u8]) {
+        self.writer.write(data);
+    }
+
+    fn finalize_into(mut self, buf: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        self.finalize_into_reset(buf)
+    }
+
+    fn finalize_into_reset(&mut self, out: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        if out.len() != 8 {
+            return Err(InvalidBufferSize);
+        }
+        let writer = std::mem::replace(
+            &mut self.writer,
+            BlockWriter::new(self.build_hasher.build_hasher()),
+        );
+        out.copy_from_slice(&writer.finish());
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.writer = BlockWriter::new(self.build_hasher.build_hasher());
+    }
+
+    fn output_size(&self) -> usize {
+        8
+    }
+
+    fn box_clone(&self) -> Box<dyn DynDigest> {
+        Box::new(self.clone())
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+// Tests
+/////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_batch;
+    use crate::{DynArrayDigestV1, DynRecordDigestV0, DynRecordDigestV1};
+    use crate::{RecordDigest, RecordDigestV1};
+    use sha3::{Shake128, Shake256};
+
+    #[test]
+    fn test_xof() {
+        let batch = test_batch();
+
+        for output_size in [8, 32, 64] {
+            let digest =
+                DynRecordDigestV1::digest(Box::new(Xof::<Shake256>::new(output_size)), &batch);
+            assert_eq!(digest.len(), output_size);
+
+            // Incremental hashing with the same output size
+            let mut d = DynRecordDigestV1::new(
+                Box::new(Xof::<Shake256>::new(output_size)),
+                &batch.schema(),
+            );
+            d.update(&batch.slice(0, 2));
+            d.update(&batch.slice(2, 1));
+            assert_eq!(d.finalize(), digest);
+        }
+
+        assert_ne!(
+            DynRecordDigestV1::digest(Box::new(Xof::<Shake128>::new(32)), &batch),
+            DynRecordDigestV1::digest(Box::new(Xof::<Shake256>::new(32)), &batch),
+        );
+        assert_ne!(
+            DynRecordDigestV0::digest(Box::new(Xof::<Shake128>::new(32)), &batch),
+            DynRecordDigestV1::digest(Box::new(Xof::<Shake128>::new(32)), &batch),
+        );
+
+        let array = batch.column(3).as_ref();
+        assert_eq!(
+            DynArrayDigestV1::digest(Box::new(Xof::<Shake128>::new(16)), array).len(),
+            16
+        );
+    }
+
+    #[test]
+    fn test_xof_nested_output_size() {
+        let batch = test_batch();
+        let digest = |output_size| {
+            DynRecordDigestV1::digest(Box::new(Xof::<Shake256>::new(output_size)), &batch)
+        };
+
+        // Short digests don't shrink digests of nested arrays, so they are prefixes of
+        // the digest of the minimum nested size
+        assert_eq!(Xof::<Shake256>::new(8).box_clone().output_size(), 32);
+        assert_eq!(digest(8)[..], digest(32)[..8]);
+        assert_eq!(digest(16)[..], digest(32)[..16]);
+
+        // Longer digests have nested digests of the same size
+        assert_eq!(Xof::<Shake256>::new(64).box_clone().output_size(), 64);
+        assert_ne!(digest(64)[..32], digest(32)[..]);
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_blake3() {
+        use digest::{Digest, ExtendableOutput};
+
+        assert_eq!(
+            Blake3::digest(b"abc").as_slice(),
+            blake3::hash(b"abc").as_bytes()
+        );
+
+        let mut hasher = Blake3::new();
+        Digest::update(&mut hasher, b"abc");
+        assert_eq!(Digest::finalize_reset(&mut hasher), Blake3::digest(b"abc"));
+        assert_eq!(hasher.finalize(), Blake3::digest(b""));
+
+        let mut xof = [0u8; 64];
+        let mut hasher = Blake3::default();
+        Update::update(&mut hasher, b"abc");
+        hasher.finalize_xof_into(&mut xof);
+        let mut expected = [0u8; 64];
+        blake3::Hasher::new()
+            .update(b"abc")
+            .finalize_xof()
+            .fill(&mut expected);
+        assert_eq!(xof, expected);
+
+        // Default-length output of BLAKE3 is a prefix of its XOF output
+        let batch = test_batch();
+        assert_eq!(
+            &DynRecordDigestV1::digest(Box::new(Xof::<Blake3>::new(32)), &batch)[..],
+            &RecordDigestV1::<Blake3>::digest(&batch)[..],
+        );
+    }
+
+    #[test]
+    fn test_var_output() {
+        let batch = test_batch();
+
+        // BLAKE2b encodes output size in its parameters, so variable-size hashers match
+        // fixed-size ones
+        assert_eq!(
+            &DynRecordDigestV1::digest(
+                Box::new(VarOutput::<blake2::Blake2bVar>::new(32).unwrap()),
+                &batch
+            )[..],
+            &RecordDigestV1::<blake2::Blake2b<digest::consts::U32>>::digest(&batch)[..],
+        );
+        assert_eq!(
+            &DynRecordDigestV1::digest(
+                Box::new(VarOutput::<blake2::Blake2bVar>::new(64).unwrap()),
+                &batch
+            )[..],
+            &RecordDigestV1::<blake2::Blake2b512>::digest(&batch)[..],
+        );
+
+        let digest = DynRecordDigestV1::digest(
+            Box::new(VarOutput::<blake2::Blake2bVar>::new(8).unwrap()),
+            &batch,
+        );
+        assert_eq!(digest.len(), 8);
+
+        // Short digests don't shrink digests of nested arrays
+        let hasher = VarOutput::<blake2::Blake2bVar>::new(8).unwrap();
+        assert_eq!(hasher.box_clone().output_size(), 32);
+
+        assert!(VarOutput::<blake2::Blake2bVar>::new(65).is_err());
+    }
+
+    #[cfg(feature = "xxh3")]
+    #[test]
+    fn test_xxh3() {
+        use crate::{ArrayDigest, ArrayDigestV1, RecordDigestV0};
+        use arrow::array::{DictionaryArray, Int8Array, StringArray};
+        use std::sync::Arc;
+
+        let batch = test_batch();
+        let digest = RecordDigestV1::<Xxh3_128>::digest(&batch);
+        assert_eq!(digest.len(), 16);
+        assert_eq!(RecordDigestV1::<Xxh3_64>::digest(&batch).len(), 8);
+        assert_ne!(&digest[..], &RecordDigestV0::<Xxh3_128>::digest(&batch)[..]);
+
+        // Same result no matter how data is split into batches
+        let mut d = RecordDigestV1::<Xxh3_128>::new(&batch.schema());
+        d.update(&batch.slice(0, 1));
+        d.update(&batch.slice(1, 2));
+        assert_eq!(d.finalize(), digest);
+
+        // Same result for dictionary-encoded data
+        let plain = StringArray::from(vec![Some("a"), None, Some("b"), Some("a")]);
+        let dict = DictionaryArray::new(
+            Int8Array::from(vec![Some(0), None, Some(1), Some(0)]),
+            Arc::new(StringArray::from(vec!["a", "b"])),
+        );
+        assert_eq!(
+            ArrayDigestV1::<Xxh3_64>::digest(&plain),
+            ArrayDigestV1::<Xxh3_64>::digest(&dict),
+        );
+    }
+
+    // Hasher that mixes in the length of every write, so results depend on how the input
+    // was split between writes
+    #[cfg(feature = "std-hasher")]
+    #[derive(Clone, Default)]
+    struct SplitSensitiveHasher(std::hash::DefaultHasher);
+
+    #[cfg(feature = "std-hasher")]
+    impl std::hash::Hasher for SplitSensitiveHasher {
+        fn finish(&self) -> u64 {
+            self.0.finish()
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.write_usize(bytes.len());
+            self.0.write(bytes);
+        }
+    }
+
+    #[cfg(feature = "std-hasher")]
+    #[test]
+    fn test_std_hasher() {
+        use crate::{ArrayDigest, ArrayDigestV0, ArrayDigestV1};
+        use arrow::array::{Array, Int64Array};
+        use std::hash::{BuildHasherDefault, DefaultHasher, RandomState};
+
+        type Hasher = StdHasher<SplitSensitiveHasher>;
+
+        // Spans multiple blocks and is split at arbitrary boundaries
+        let array = Int64Array::from_iter_values(0..5000);
+        let digest = ArrayDigestV1::<Hasher>::digest(&array);
+        assert_eq!(digest.len(), 8);
+
+        let mut d = ArrayDigestV1::<Hasher>::new(array.data_type());
+        d.update(&array.slice(0, 1), None);
+        d.update(&array.slice(1, 1234), None);
+        d.update(&array.slice(1235, 3765), None);
+        assert_eq!(d.finalize(), digest);
+
+        assert_ne!(digest, ArrayDigestV0::<Hasher>::digest(&array));
+
+        // Builders produce the same results as default-constructed hashers
+        let batch = test_batch();
+        assert_eq!(
+            &DynRecordDigestV1::digest(
+                Box::new(StdBuildHasher::new(BuildHasherDefault::<
+                    SplitSensitiveHasher,
+                >::default())),
+                &batch
+            )[..],
+            &RecordDigestV1::<Hasher>::digest(&batch)[..],
+        );
+
+        // Builders with random state are consistent across clones
+        let state = RandomState::new();
+        assert_eq!(
+            DynRecordDigestV1::digest(Box::new(StdBuildHasher::new(state.clone())), &batch),
+            DynRecordDigestV1::digest(Box::new(StdBuildHasher::new(state)), &batch),
+        );
+        assert_eq!(
+            &DynArrayDigestV1::digest(
+                Box::new(StdBuildHasher::new(
+                    BuildHasherDefault::<DefaultHasher>::default()
+                )),
+                &array
+            )[..],
+            &ArrayDigestV1::<StdHasher<DefaultHasher>>::digest(&array)[..],
+        );
+    }
+}
@@ -1,22 +1,25 @@
-use crate::ArrayDigest;
+use crate::utils::BitmapDigest;
+use crate::{ArrayDigest, DigestError};
 use arrow::{
     array::{
-        Array, BinaryArray, BinaryViewArray, BooleanArray, FixedSizeBinaryArray,
-        FixedSizeListArray, GenericBinaryArray, GenericListArray, GenericStringArray,
-        LargeBinaryArray, LargeListArray, LargeStringArray, ListArray, OffsetSizeTrait,
-        StringArray, StringViewArray,
+        Array, AsArray, BinaryArray, BinaryViewArray, BooleanArray, FixedSizeBinaryArray,
+        FixedSizeListArray, GenericBinaryArray, GenericListArray, GenericListViewArray,
+        GenericStringArray, LargeBinaryArray, LargeListArray, LargeStringArray, ListArray,
+        MapArray, OffsetSizeTrait, PrimitiveArray, RunArray, StringArray, StringViewArray,
+        StructArray, UnionArray,
     },
     buffer::NullBuffer,
-    datatypes::DataType,
+    datatypes::{
+        ArrowNativeType, ArrowPrimitiveType, DataType, Int16Type, Int32Type, Int64Type,
+        IntervalDayTimeType, IntervalMonthDayNanoType, IntervalUnit, IntervalYearMonthType,
+        RunEndIndexType, UInt64Type,
+    },
 };
 use digest::{Digest, Output, OutputSizeUser};
 
 /////////////////////////////////////////////////////////////////////////////////////////
-pub struct ArrayDigestV0<Dig: Digest> {
-    hasher: Dig,
-}
 
-/////////////////////////////////////////////////////////////////////////////////////////
+pub struct ArrayDigestV0<Dig: Digest>(ArrayDigestImpl<Dig>);
 
 impl<Dig: Digest> OutputSizeUser for ArrayDigestV0<Dig> {
     type OutputSize = Dig::OutputSize;
@@ -30,26 +33,192 @@ impl<Dig: Digest> ArrayDigest for ArrayDigestV0<Dig> {
     }
 
     fn new(data_type: &DataType) -> Self {
-        let mut hasher = Dig::new();
-        crate::schema_digest::hash_data_type(data_type, &mut hasher);
-        Self { hasher }
+        Self(ArrayDigestImpl::new(
+            DigestVersion::V0,
+            data_type,
+            Dig::new(),
+        ))
     }
 
     fn update(&mut self, array: &dyn Array, parent_null_bitmap: Option<&NullBuffer>) {
-        let array_data = array.to_data();
-        let combined_null_bitmap_val =
-            crate::utils::maybe_combine_null_buffers(parent_null_bitmap, array_data.nulls());
-        let combined_null_bitmap = combined_null_bitmap_val.as_option();
+        self.0.update(array, parent_null_bitmap);
+    }
 
-        let data_type = array.data_type();
+    fn try_update(
+        &mut self,
+        array: &dyn Array,
+        parent_null_bitmap: Option<&NullBuffer>,
+    ) -> Result<(), DigestError> {
+        self.0.try_update(array, parent_null_bitmap)
+    }
+
+    fn finalize(self) -> Output<Dig> {
+        self.0.finalize()
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+// Differs from V0 in encoding of nulls: validity of every value is hashed as a separate
+// bitmap stream, while nulls don't contribute any bytes to the stream of values. Booleans
+// are hashed as a bitmap stream too instead of a byte per value.
+pub struct ArrayDigestV1<Dig: Digest>(ArrayDigestImpl<Dig>);
+
+impl<Dig: Digest> OutputSizeUser for ArrayDigestV1<Dig> {
+    type OutputSize = Dig::OutputSize;
+}
+
+impl<Dig: Digest> ArrayDigest for ArrayDigestV1<Dig> {
+    fn digest(array: &dyn Array) -> Output<Dig> {
+        let mut d = Self::new(array.data_type());
+        d.update(array, None);
+        d.finalize()
+    }
+
+    fn new(data_type: &DataType) -> Self {
+        Self(ArrayDigestImpl::new(
+            DigestVersion::V1,
+            data_type,
+            Dig::new(),
+        ))
+    }
+
+    fn update(&mut self, array: &dyn Array, parent_null_bitmap: Option<&NullBuffer>) {
+        self.0.update(array, parent_null_bitmap);
+    }
+
+    fn try_update(
+        &mut self,
+        array: &dyn Array,
+        parent_null_bitmap: Option<&NullBuffer>,
+    ) -> Result<(), DigestError> {
+        self.0.try_update(array, parent_null_bitmap)
+    }
+
+    fn finalize(self) -> Output<Dig> {
+        self.0.finalize()
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DigestVersion {
+    V0,
+    V1,
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+pub(crate) struct ArrayDigestImpl<H: crate::hasher::HashSink> {
+    version: DigestVersion,
+    // Type the digest was created with along with its encoding, used to validate arrays
+    data_type: DataType,
+    type_encoding: Vec<u8>,
+    hasher: H,
+    // Validity of values (V1+)
+    validity: Option<BitmapDigest<H>>,
+    // Values of booleans (V1+)
+    bool_values: Option<BitmapDigest<H>>,
+    // Digests of leaf columns for struct types
+    children: Vec<ArrayDigestImpl<H>>,
+}
+
+impl<H: crate::hasher::HashSink> ArrayDigestImpl<H> {
+    pub fn new(version: DigestVersion, data_type: &DataType, mut hasher: H) -> Self {
+        let type_encoding = crate::schema_digest::encode_valid_data_type(data_type);
+        hasher.update(&type_encoding);
+        let validity = match version {
+            DigestVersion::V0 => None,
+            DigestVersion::V1 => Some(BitmapDigest::new(hasher.fork())),
+        };
+        let bool_values = match version {
+            DigestVersion::V1 if Self::has_bool_values(data_type) => {
+                Some(BitmapDigest::new(hasher.fork()))
+            }
+            _ => None,
+        };
+        let children = Self::new_children(version, data_type, &hasher);
+        Self {
+            version,
+            data_type: data_type.clone(),
+            type_encoding,
+            hasher,
+            validity,
+            bool_values,
+            children,
+        }
+    }
+
+    pub fn try_update(
+        &mut self,
+        array: &dyn Array,
+        parent_null_bitmap: Option<&NullBuffer>,
+    ) -> Result<(), DigestError> {
+        crate::traits::check_array(array, parent_null_bitmap)?;
+        self.check_array_type(array.data_type())?;
+        self.update(array, parent_null_bitmap);
+        Ok(())
+    }
+
+    // Arrays may use a different but logically equivalent encoding of the type (e.g. `Utf8`
+    // and `LargeUtf8`) as long as it is hashed identically, and in V0 nested structs are
+    // flattened identically
+    fn check_array_type(&self, data_type: &DataType) -> Result<(), DigestError> {
+        if *data_type != self.data_type
+            && (crate::encode_data_type(data_type)? != self.type_encoding
+                || self.version == DigestVersion::V0
+                    && !crate::utils::flattens_identically(&self.data_type, data_type, false))
+        {
+            return Err(DigestError::TypeMismatch {
+                expected: self.data_type.clone(),
+                actual: data_type.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    // V0 keeps combining the null bitmap passed by the caller the way it always did, so that
+    // existing hashes don't change
+    pub fn update(&mut self, array: &dyn Array, parent_null_bitmap: Option<&NullBuffer>) {
+        self.update_with(array, parent_null_bitmap, self.version == DigestVersion::V0);
+    }
+
+    // Arrays nested into other arrays treat a value as null when it's null in any of the
+    // parents, so the result doesn't depend on how the data is split into batches
+    fn update_nested(&mut self, array: &dyn Array, parent_null_bitmap: Option<&NullBuffer>) {
+        self.update_with(array, parent_null_bitmap, false);
+    }
 
-        #[inline]
-        fn unsupported(data_type: &DataType) -> ! {
-            unimplemented!("Type {} is not yet supported", data_type);
+    fn update_with(
+        &mut self,
+        array: &dyn Array,
+        parent_null_bitmap: Option<&NullBuffer>,
+        legacy_nulls: bool,
+    ) {
+        // Encoded arrays are combined with the nulls of their logical values, so they are
+        // hashed exactly like the plain arrays. Buffers without nulls are dropped, as legacy
+        // combination treats them differently from absent ones.
+        let null_bitmap = match array.data_type() {
+            DataType::Dictionary(..) | DataType::RunEndEncoded(..) => array.logical_nulls(),
+            _ => array.nulls().cloned(),
         }
+        .filter(|nulls| nulls.null_count() != 0);
+        let combined_null_bitmap_val = if legacy_nulls {
+            crate::utils::maybe_combine_null_buffers_v0(parent_null_bitmap, null_bitmap.as_ref())
+        } else {
+            crate::utils::maybe_combine_null_buffers(parent_null_bitmap, null_bitmap.as_ref())
+        };
+
+        self.hash_array(array, combined_null_bitmap_val.as_option());
+    }
+
+    // Hashes the array using the final null bitmap, ignoring nulls of the array itself
+    fn hash_array(&mut self, array: &dyn Array, combined_null_bitmap: Option<&NullBuffer>) {
+        let data_type = array.data_type();
 
         match data_type {
-            DataType::Null => unsupported(data_type),
+            DataType::Null => self.hash_array_null(array),
             DataType::Boolean => self.hash_array_bool(array, combined_null_bitmap),
             DataType::Int8 | DataType::UInt8 => {
                 self.hash_fixed_size(array, 1, combined_null_bitmap)
@@ -71,8 +240,33 @@ impl<Dig: Digest> ArrayDigest for ArrayDigestV0<Dig> {
             DataType::Date64 => self.hash_fixed_size(array, 8, combined_null_bitmap),
             DataType::Time32(_) => self.hash_fixed_size(array, 4, combined_null_bitmap),
             DataType::Time64(_) => self.hash_fixed_size(array, 8, combined_null_bitmap),
-            DataType::Duration(_) => unsupported(data_type),
-            DataType::Interval(_) => unsupported(data_type),
+            DataType::Duration(_) => self.hash_fixed_size(array, 8, combined_null_bitmap),
+            DataType::Interval(IntervalUnit::YearMonth) => self.hash_array_interval(
+                array.as_primitive::<IntervalYearMonthType>(),
+                combined_null_bitmap,
+                |months| months.to_le_bytes(),
+            ),
+            DataType::Interval(IntervalUnit::DayTime) => self.hash_array_interval(
+                array.as_primitive::<IntervalDayTimeType>(),
+                combined_null_bitmap,
+                |v| {
+                    let mut buf = [0u8; 8];
+                    buf[0..4].copy_from_slice(&v.days.to_le_bytes());
+                    buf[4..8].copy_from_slice(&v.milliseconds.to_le_bytes());
+                    buf
+                },
+            ),
+            DataType::Interval(IntervalUnit::MonthDayNano) => self.hash_array_interval(
+                array.as_primitive::<IntervalMonthDayNanoType>(),
+                combined_null_bitmap,
+                |v| {
+                    let mut buf = [0u8; 16];
+                    buf[0..4].copy_from_slice(&v.months.to_le_bytes());
+                    buf[4..8].copy_from_slice(&v.days.to_le_bytes());
+                    buf[8..16].copy_from_slice(&v.nanoseconds.to_le_bytes());
+                    buf
+                },
+            ),
             DataType::Binary => self.hash_array_binary(
                 array.as_any().downcast_ref::<BinaryArray>().unwrap(),
                 combined_null_bitmap,
@@ -113,36 +307,158 @@ impl<Dig: Digest> ArrayDigest for ArrayDigestV0<Dig> {
                 array.as_any().downcast_ref::<LargeListArray>().unwrap(),
                 combined_null_bitmap,
             ),
-            DataType::ListView(_) | DataType::LargeListView(_) => unsupported(data_type),
+            DataType::ListView(_) => {
+                self.hash_array_list_view(array.as_list_view::<i32>(), combined_null_bitmap)
+            }
+            DataType::LargeListView(_) => {
+                self.hash_array_list_view(array.as_list_view::<i64>(), combined_null_bitmap)
+            }
             DataType::FixedSizeList(..) => self.hash_array_list_fixed(
                 array.as_any().downcast_ref::<FixedSizeListArray>().unwrap(),
                 combined_null_bitmap,
             ),
-            // TODO: Should structs be handled by array digest to allow use without record hasher?
-            DataType::Struct(_) => panic!(
-                "Structs are currently flattened by RecordDigest and cannot be processed by ArrayDigest"
-            ),
-            DataType::Union(_, _) => unsupported(data_type),
-            DataType::Dictionary(..) => unsupported(data_type),
+            DataType::Struct(_) => self.hash_array_struct(array.as_struct(), combined_null_bitmap),
+            DataType::Union(_, _) => self.hash_array_union(array.as_union(), combined_null_bitmap),
+            DataType::Dictionary(..) => self.hash_array_dictionary(array, combined_null_bitmap),
             DataType::Decimal32(_, _) => self.hash_fixed_size(array, 4, combined_null_bitmap),
             DataType::Decimal64(_, _) => self.hash_fixed_size(array, 8, combined_null_bitmap),
             DataType::Decimal128(_, _) => self.hash_fixed_size(array, 16, combined_null_bitmap),
             DataType::Decimal256(_, _) => self.hash_fixed_size(array, 32, combined_null_bitmap),
-            DataType::Map(..) => unsupported(data_type),
-            DataType::RunEndEncoded(..) => unsupported(data_type),
+            DataType::Map(..) => self.hash_array_map(array.as_map(), combined_null_bitmap),
+            DataType::RunEndEncoded(run_ends_field, _) => match run_ends_field.data_type() {
+                DataType::Int16 => self
+                    .hash_array_run_end_encoded(array.as_run::<Int16Type>(), combined_null_bitmap),
+                DataType::Int32 => self
+                    .hash_array_run_end_encoded(array.as_run::<Int32Type>(), combined_null_bitmap),
+                DataType::Int64 => self
+                    .hash_array_run_end_encoded(array.as_run::<Int64Type>(), combined_null_bitmap),
+                // Arrow validates run ends to be one of the above when creating arrays
+                run_ends_type => unreachable!("Run ends of type {run_ends_type} are not valid"),
+            },
         }
     }
 
-    fn finalize(self) -> Output<Dig> {
+    pub fn finalize(mut self) -> H::Output {
+        if let Some(validity) = self.validity {
+            let validity_hash = validity.finalize();
+            self.hasher.update(validity_hash);
+        }
+        if let Some(bool_values) = self.bool_values {
+            let bool_values_hash = bool_values.finalize();
+            self.hasher.update(bool_values_hash);
+        }
+        for c in self.children {
+            let column_hash = c.finalize();
+            self.hasher.update(column_hash);
+        }
         self.hasher.finalize()
     }
-}
-
-/////////////////////////////////////////////////////////////////////////////////////////
 
-impl<Dig: Digest> ArrayDigestV0<Dig> {
     const NULL_MARKER: [u8; 1] = [0];
 
+    // Records the value as valid - V0 doesn't hash anything for valid values
+    #[inline]
+    fn hash_valid(&mut self) {
+        if let Some(validity) = &mut self.validity {
+            validity.append(true);
+        }
+    }
+
+    #[inline]
+    fn hash_valid_n(&mut self, n: usize) {
+        if let Some(validity) = &mut self.validity {
+            validity.append_n(n, true);
+        }
+    }
+
+    #[inline]
+    fn hash_null(&mut self) {
+        match &mut self.validity {
+            None => self.hasher.update(Self::NULL_MARKER),
+            Some(validity) => validity.append(false),
+        }
+    }
+
+    // Whether booleans can be hashed by this digest directly or as items of lists and values
+    // of encoded arrays
+    fn has_bool_values(data_type: &DataType) -> bool {
+        match data_type {
+            DataType::Boolean => true,
+            DataType::List(field)
+            | DataType::LargeList(field)
+            | DataType::ListView(field)
+            | DataType::LargeListView(field)
+            | DataType::FixedSizeList(field, _) => Self::has_bool_values(field.data_type()),
+            DataType::Dictionary(_, value_type) => Self::has_bool_values(value_type),
+            DataType::RunEndEncoded(_, values_field) => {
+                Self::has_bool_values(values_field.data_type())
+            }
+            _ => false,
+        }
+    }
+
+    // Items of lists and values of encoded arrays are hashed by the same digest recursively,
+    // so a struct found anywhere under them stores digests of its leaf columns here
+    fn new_children(version: DigestVersion, data_type: &DataType, hasher: &H) -> Vec<Self> {
+        match data_type {
+            // V1+ keeps a digest per struct field, nesting them the same way as the fields
+            DataType::Struct(fields) if version != DigestVersion::V0 => fields
+                .iter()
+                .map(|field| Self::new(version, field.data_type(), hasher.fork()))
+                .collect(),
+            // Structs are flattened into leaf columns the same way RecordDigestV0 does it
+            DataType::Struct(fields) => {
+                let mut children = Vec::new();
+                crate::utils::walk_nested_fields(
+                    fields,
+                    0,
+                    &mut |field, _| match field.data_type() {
+                        DataType::Struct(_) => (),
+                        _ => children.push(Self::new(version, field.data_type(), hasher.fork())),
+                    },
+                );
+                children
+            }
+            DataType::List(field)
+            | DataType::LargeList(field)
+            | DataType::ListView(field)
+            | DataType::LargeListView(field)
+            | DataType::FixedSizeList(field, _) => {
+                Self::new_children(version, field.data_type(), hasher)
+            }
+            // Keys and values are hashed separately to allow them to be nested types too
+            DataType::Map(entries_field, _) => {
+                let DataType::Struct(entries_fields) = entries_field.data_type() else {
+                    panic!(
+                        "Map entries must be a struct but got {}",
+                        entries_field.data_type()
+                    );
+                };
+                entries_fields
+                    .iter()
+                    .map(|field| Self::new(version, field.data_type(), hasher.fork()))
+                    .collect()
+            }
+            // Every union field is hashed separately so that sparse and dense layouts match
+            DataType::Union(fields, _) => fields
+                .iter()
+                .map(|(_, field)| Self::new(version, field.data_type(), hasher.fork()))
+                .collect(),
+            DataType::Dictionary(_, value_type) => Self::new_children(version, value_type, hasher),
+            DataType::RunEndEncoded(_, values_field) => {
+                Self::new_children(version, values_field.data_type(), hasher)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    // Every value of a null-typed array is a null, regardless of any parent bitmaps
+    fn hash_array_null(&mut self, array: &dyn Array) {
+        for _ in 0..array.len() {
+            self.hash_null();
+        }
+    }
+
     fn hash_fixed_size(
         &mut self,
         array: &dyn Array,
@@ -164,8 +480,11 @@ impl<Dig: Digest> ArrayDigestV0<Dig> {
             &array_data.buffers()[0].as_slice()[data_start..data_end]
         };
 
+        let slice = crate::utils::to_little_endian(slice, item_size);
+
         match null_bitmap {
             None => {
+                self.hash_valid_n(array.len());
                 // In case of no nulls we can hash the whole buffer in one go
                 self.hasher.update(slice);
             }
@@ -173,22 +492,56 @@ impl<Dig: Digest> ArrayDigestV0<Dig> {
                 // Otherwise have to go element-by-element
                 for i in 0..array.len() {
                     if null_bitmap.is_valid(i) {
+                        self.hash_valid();
                         let pos = i * item_size;
                         self.hasher.update(&slice[pos..pos + item_size]);
                     } else {
-                        self.hasher.update(Self::NULL_MARKER);
+                        self.hash_null();
+                    }
+                }
+            }
+        }
+    }
+
+    // Intervals are hashed component by component rather than as raw bytes of native structs
+    // to not depend on their in-memory layout
+    fn hash_array_interval<T: ArrowPrimitiveType, const N: usize>(
+        &mut self,
+        array: &PrimitiveArray<T>,
+        null_bitmap: Option<&NullBuffer>,
+        to_le_bytes: impl Fn(T::Native) -> [u8; N],
+    ) {
+        match null_bitmap {
+            None => {
+                self.hash_valid_n(array.len());
+                for value in array.values() {
+                    self.hasher.update(to_le_bytes(*value));
+                }
+            }
+            Some(null_bitmap) => {
+                for (i, value) in array.values().iter().enumerate() {
+                    if null_bitmap.is_valid(i) {
+                        self.hash_valid();
+                        self.hasher.update(to_le_bytes(*value));
+                    } else {
+                        self.hash_null();
                     }
                 }
             }
         }
     }
 
-    // TODO: PERF: Hashing bool bitmaps is expensive because we have to deal with offsets
+    // V0 hashes a byte per value, while V1+ hashes bit-packed values
     fn hash_array_bool(&mut self, array: &dyn Array, null_bitmap: Option<&NullBuffer>) {
         let bool_array = array.as_any().downcast_ref::<BooleanArray>().unwrap();
 
+        if let (Some(validity), Some(bool_values)) = (&mut self.validity, &mut self.bool_values) {
+            return Self::hash_array_bool_packed(bool_array, null_bitmap, validity, bool_values);
+        }
+
         match null_bitmap {
             None => {
+                self.hash_valid_n(bool_array.len());
                 for i in 0..bool_array.len() {
                     // Safety: boundary check is right above
                     let value = unsafe { bool_array.value_unchecked(i) };
@@ -198,17 +551,38 @@ impl<Dig: Digest> ArrayDigestV0<Dig> {
             Some(null_bitmap) => {
                 for i in 0..bool_array.len() {
                     if null_bitmap.is_valid(i) {
+                        self.hash_valid();
                         // Safety: boundary check is right above
                         let value = unsafe { bool_array.value_unchecked(i) };
                         self.hasher.update([value as u8 + 1]);
                     } else {
-                        self.hasher.update(Self::NULL_MARKER);
+                        self.hash_null();
                     }
                 }
             }
         }
     }
 
+    // Values are appended as a bitmap a word at a time, with values of nulls cleared to keep
+    // the result independent of what is stored in the null slots
+    fn hash_array_bool_packed(
+        array: &BooleanArray,
+        null_bitmap: Option<&NullBuffer>,
+        validity: &mut BitmapDigest<H>,
+        bool_values: &mut BitmapDigest<H>,
+    ) {
+        match null_bitmap {
+            None => {
+                validity.append_n(array.len(), true);
+                bool_values.append_bitmap(array.values());
+            }
+            Some(null_bitmap) => {
+                validity.append_bitmap(null_bitmap.inner());
+                bool_values.append_bitmap(&(array.values() & null_bitmap.inner()));
+            }
+        }
+    }
+
     fn hash_array_string<OffsetSize: OffsetSizeTrait>(
         &mut self,
         array: &GenericStringArray<OffsetSize>,
@@ -216,6 +590,7 @@ impl<Dig: Digest> ArrayDigestV0<Dig> {
     ) {
         match null_bitmap {
             None => {
+                self.hash_valid_n(array.len());
                 for i in 0..array.len() {
                     let s = array.value(i);
                     self.hasher.update((s.len() as u64).to_le_bytes());
@@ -225,11 +600,12 @@ impl<Dig: Digest> ArrayDigestV0<Dig> {
             Some(null_bitmap) => {
                 for i in 0..array.len() {
                     if null_bitmap.is_valid(i) {
+                        self.hash_valid();
                         let s = array.value(i);
                         self.hasher.update((s.len() as u64).to_le_bytes());
                         self.hasher.update(s.as_bytes());
                     } else {
-                        self.hasher.update(Self::NULL_MARKER);
+                        self.hash_null();
                     }
                 }
             }
@@ -243,6 +619,7 @@ impl<Dig: Digest> ArrayDigestV0<Dig> {
     ) {
         match null_bitmap {
             None => {
+                self.hash_valid_n(array.len());
                 for i in 0..array.len() {
                     let s = array.value(i);
                     self.hasher.update((s.len() as u64).to_le_bytes());
@@ -252,11 +629,12 @@ impl<Dig: Digest> ArrayDigestV0<Dig> {
             Some(null_bitmap) => {
                 for i in 0..array.len() {
                     if null_bitmap.is_valid(i) {
+                        self.hash_valid();
                         let s = array.value(i);
                         self.hasher.update((s.len() as u64).to_le_bytes());
                         self.hasher.update(s.as_bytes());
                     } else {
-                        self.hasher.update(Self::NULL_MARKER);
+                        self.hash_null();
                     }
                 }
             }
@@ -270,6 +648,7 @@ impl<Dig: Digest> ArrayDigestV0<Dig> {
     ) {
         match null_bitmap {
             None => {
+                self.hash_valid_n(array.len());
                 for i in 0..array.len() {
                     let slice = array.value(i);
                     self.hasher.update((slice.len() as u64).to_le_bytes());
@@ -279,11 +658,12 @@ impl<Dig: Digest> ArrayDigestV0<Dig> {
             Some(null_bitmap) => {
                 for i in 0..array.len() {
                     if null_bitmap.is_valid(i) {
+                        self.hash_valid();
                         let slice = array.value(i);
                         self.hasher.update((slice.len() as u64).to_le_bytes());
                         self.hasher.update(slice);
                     } else {
-                        self.hasher.update(Self::NULL_MARKER);
+                        self.hash_null();
                     }
                 }
             }
@@ -297,6 +677,7 @@ impl<Dig: Digest> ArrayDigestV0<Dig> {
     ) {
         match null_bitmap {
             None => {
+                self.hash_valid_n(array.len());
                 for i in 0..array.len() {
                     let slice = array.value(i);
                     self.hasher.update((slice.len() as u64).to_le_bytes());
@@ -306,11 +687,12 @@ impl<Dig: Digest> ArrayDigestV0<Dig> {
             Some(null_bitmap) => {
                 for i in 0..array.len() {
                     if null_bitmap.is_valid(i) {
+                        self.hash_valid();
                         let slice = array.value(i);
                         self.hasher.update((slice.len() as u64).to_le_bytes());
                         self.hasher.update(slice);
                     } else {
-                        self.hasher.update(Self::NULL_MARKER);
+                        self.hash_null();
                     }
                 }
             }
@@ -325,6 +707,7 @@ impl<Dig: Digest> ArrayDigestV0<Dig> {
     ) {
         match null_bitmap {
             None => {
+                self.hash_valid_n(array.len());
                 for i in 0..array.len() {
                     let slice = array.value(i);
                     self.hasher.update((size as u64).to_le_bytes());
@@ -334,15 +717,114 @@ impl<Dig: Digest> ArrayDigestV0<Dig> {
             Some(null_bitmap) => {
                 for i in 0..array.len() {
                     if null_bitmap.is_valid(i) {
+                        self.hash_valid();
                         let slice = array.value(i);
                         self.hasher.update((size as u64).to_le_bytes());
                         self.hasher.update(slice);
                     } else {
-                        self.hasher.update(Self::NULL_MARKER);
+                        self.hash_null();
+                    }
+                }
+            }
+        }
+    }
+
+    // Dictionaries are hashed as their decoded values to be indistinguishable from plain arrays
+    fn hash_array_dictionary(&mut self, array: &dyn Array, null_bitmap: Option<&NullBuffer>) {
+        let dict_array = array.as_any_dictionary();
+
+        let decoded = arrow::compute::take(dict_array.values().as_ref(), dict_array.keys(), None)
+            .expect("Failed to decode dictionary array");
+
+        self.hash_array(decoded.as_ref(), null_bitmap);
+    }
+
+    // Run-end encoded arrays are hashed as their logical values. To avoid hashing every
+    // element separately runs are expanded into chunks of bounded size, so the memory
+    // overhead doesn't depend on the logical length of the array.
+    fn hash_array_run_end_encoded<R: RunEndIndexType>(
+        &mut self,
+        array: &RunArray<R>,
+        null_bitmap: Option<&NullBuffer>,
+    ) {
+        const CHUNK_SIZE: usize = 8 * 1024;
+
+        let run_ends = array.run_ends();
+        let mut physical_index = array.get_start_physical_index();
+        let mut chunk_start = 0;
+
+        while chunk_start < array.len() {
+            let chunk_end = (chunk_start + CHUNK_SIZE).min(array.len());
+            let mut indices = Vec::with_capacity(chunk_end - chunk_start);
+
+            let mut pos = chunk_start;
+            while pos < chunk_end {
+                // Run ends are absolute so we need to adjust them to the logical slice
+                let run_end = (run_ends.values()[physical_index].as_usize() - run_ends.offset())
+                    .min(run_ends.len());
+                let end = run_end.min(chunk_end);
+
+                indices.extend(std::iter::repeat_n(physical_index as u64, end - pos));
+
+                if end == run_end {
+                    physical_index += 1;
+                }
+                pos = end;
+            }
+
+            let chunk = arrow::compute::take(
+                array.values().as_ref(),
+                &PrimitiveArray::<UInt64Type>::from(indices),
+                None,
+            )
+            .expect("Failed to expand run-end encoded array");
+
+            let chunk_null_bitmap =
+                null_bitmap.map(|nb| nb.slice(chunk_start, chunk_end - chunk_start));
+            self.hash_array(chunk.as_ref(), chunk_null_bitmap.as_ref());
+
+            chunk_start = chunk_end;
+        }
+    }
+
+    // Leaf columns of a struct receive combined nullability of all parents and are hashed
+    // separately to stay independent of how data is split into batches
+    fn hash_array_struct(&mut self, array: &StructArray, null_bitmap: Option<&NullBuffer>) {
+        if self.version != DigestVersion::V0 {
+            return self.hash_array_struct_nested(array, null_bitmap);
+        }
+
+        let mut col_index = 0;
+        crate::utils::walk_nested_columns(
+            array.columns().iter(),
+            null_bitmap,
+            false,
+            &mut |array, parent_null_bitmap| {
+                self.children[col_index].update_nested(array.as_ref(), parent_null_bitmap);
+                col_index += 1;
+            },
+        );
+    }
+
+    // Validity of the struct itself is recorded before passing the combined nullability
+    // down to the fields, so a null struct differs from a struct with all fields being null
+    fn hash_array_struct_nested(&mut self, array: &StructArray, null_bitmap: Option<&NullBuffer>) {
+        match null_bitmap {
+            None => self.hash_valid_n(array.len()),
+            Some(null_bitmap) => {
+                for i in 0..array.len() {
+                    if null_bitmap.is_valid(i) {
+                        self.hash_valid();
+                    } else {
+                        self.hash_null();
                     }
                 }
             }
         }
+
+        for (child, column) in self.children.iter_mut().zip(array.columns()) {
+            child.update_nested(column.as_ref(), null_bitmap);
+        }
     }
 
     fn hash_array_list<Off: OffsetSizeTrait>(
@@ -353,23 +835,162 @@ impl<Dig: Digest> ArrayDigestV0<Dig> {
         match null_bitmap {
             None => {
                 for i in 0..array.len() {
+                    self.hash_valid();
+                    let sub_array = array.value(i);
+                    self.hasher.update((sub_array.len() as u64).to_le_bytes());
+                    self.update_nested(sub_array.as_ref(), None);
+                }
+            }
+            Some(null_bitmap) => {
+                for i in 0..array.len() {
+                    if null_bitmap.is_valid(i) {
+                        self.hash_valid();
+                        let sub_array = array.value(i);
+                        self.hasher.update((sub_array.len() as u64).to_le_bytes());
+                        self.update_nested(sub_array.as_ref(), None);
+                    } else {
+                        self.hash_null();
+                    }
+                }
+            }
+        }
+    }
+
+    // Views are hashed by their logical windows, making them indistinguishable from lists
+    // regardless of the order or overlap of views
+    fn hash_array_list_view<Off: OffsetSizeTrait>(
+        &mut self,
+        array: &GenericListViewArray<Off>,
+        null_bitmap: Option<&NullBuffer>,
+    ) {
+        match null_bitmap {
+            None => {
+                for i in 0..array.len() {
+                    self.hash_valid();
                     let sub_array = array.value(i);
                     self.hasher.update((sub_array.len() as u64).to_le_bytes());
-                    self.update(sub_array.as_ref(), None);
+                    self.update_nested(sub_array.as_ref(), None);
                 }
             }
             Some(null_bitmap) => {
                 for i in 0..array.len() {
                     if null_bitmap.is_valid(i) {
+                        self.hash_valid();
                         let sub_array = array.value(i);
                         self.hasher.update((sub_array.len() as u64).to_le_bytes());
-                        self.update(sub_array.as_ref(), None);
+                        self.update_nested(sub_array.as_ref(), None);
+                    } else {
+                        self.hash_null();
+                    }
+                }
+            }
+        }
+    }
+
+    fn hash_array_map(&mut self, array: &MapArray, null_bitmap: Option<&NullBuffer>) {
+        let offsets = array.value_offsets();
+
+        // Number of entries and nulls are hashed by the map's own hasher
+        match null_bitmap {
+            None => {
+                self.hash_valid_n(array.len());
+                for i in 0..array.len() {
+                    let num_entries = offsets[i + 1] - offsets[i];
+                    self.hasher.update((num_entries as u64).to_le_bytes());
+                }
+            }
+            Some(null_bitmap) => {
+                for i in 0..array.len() {
+                    if null_bitmap.is_valid(i) {
+                        self.hash_valid();
+                        let num_entries = offsets[i + 1] - offsets[i];
+                        self.hasher.update((num_entries as u64).to_le_bytes());
                     } else {
-                        self.hasher.update(Self::NULL_MARKER);
+                        self.hash_null();
+                    }
+                }
+            }
+        }
+
+        // Keys and values of valid maps are hashed by child digests in bulk
+        let valid_slices: Vec<(usize, usize)> = match null_bitmap {
+            None => vec![(0, array.len())],
+            Some(null_bitmap) => null_bitmap.valid_slices().collect(),
+        };
+
+        for (start, end) in valid_slices {
+            let entries_start = offsets[start] as usize;
+            let entries_len = offsets[end] as usize - entries_start;
+
+            self.children[0].update_nested(
+                array.keys().slice(entries_start, entries_len).as_ref(),
+                None,
+            );
+            self.children[1].update_nested(
+                array.values().slice(entries_start, entries_len).as_ref(),
+                None,
+            );
+        }
+    }
+
+    fn hash_array_union(&mut self, array: &UnionArray, null_bitmap: Option<&NullBuffer>) {
+        // Map type IDs to positions of the corresponding fields, so that only the order of
+        // fields matters and not the IDs assigned to them
+        let mut child_indices = [None; 128];
+        for (child_index, (type_id, _)) in array.fields().iter().enumerate() {
+            let slot = usize::try_from(type_id)
+                .ok()
+                .and_then(|i| child_indices.get_mut(i))
+                .expect("Union type IDs must be non-negative");
+            *slot = Some(child_index);
+        }
+        let child_index = |type_id: i8| {
+            usize::try_from(type_id)
+                .ok()
+                .and_then(|i| child_indices.get(i).copied().flatten())
+                .expect("Union array has a type ID that is not declared in its fields")
+        };
+
+        // Field positions and nulls are hashed by the union's own hasher, while values are passed
+        // to digests of selected children in runs of consecutive values of the same child
+        let mut run: Option<(i8, usize, usize)> = None;
+
+        for i in 0..array.len() {
+            if null_bitmap.is_some_and(|nb| nb.is_null(i)) {
+                self.hash_null();
+                continue;
+            }
+
+            let type_id = array.type_id(i);
+            let value_offset = array.value_offset(i);
+            self.hash_valid();
+            self.hasher
+                .update((child_index(type_id) as u64).to_le_bytes());
+
+            match &mut run {
+                Some((run_type_id, run_start, run_len))
+                    if *run_type_id == type_id && *run_start + *run_len == value_offset =>
+                {
+                    *run_len += 1;
+                }
+                _ => {
+                    if let Some((run_type_id, run_start, run_len)) = run {
+                        self.children[child_index(run_type_id)].update_nested(
+                            array.child(run_type_id).slice(run_start, run_len).as_ref(),
+                            None,
+                        );
                     }
+                    run = Some((type_id, value_offset, 1));
                 }
             }
         }
+
+        if let Some((run_type_id, run_start, run_len)) = run {
+            self.children[child_index(run_type_id)].update_nested(
+                array.child(run_type_id).slice(run_start, run_len).as_ref(),
+                None,
+            );
+        }
     }
 
     fn hash_array_list_fixed(
@@ -380,19 +1001,21 @@ impl<Dig: Digest> ArrayDigestV0<Dig> {
         match null_bitmap {
             None => {
                 for i in 0..array.len() {
+                    self.hash_valid();
                     let sub_array = array.value(i);
                     self.hasher.update((sub_array.len() as u64).to_le_bytes());
-                    self.update(sub_array.as_ref(), None);
+                    self.update_nested(sub_array.as_ref(), None);
                 }
             }
             Some(null_bitmap) => {
                 for i in 0..array.len() {
                     if null_bitmap.is_valid(i) {
+                        self.hash_valid();
                         let sub_array = array.value(i);
                         self.hasher.update((sub_array.len() as u64).to_le_bytes());
-                        self.update(sub_array.as_ref(), None);
+                        self.update_nested(sub_array.as_ref(), None);
                     } else {
-                        self.hasher.update(Self::NULL_MARKER);
+                        self.hash_null();
                     }
                 }
             }
@@ -407,15 +1030,24 @@ impl<Dig: Digest> ArrayDigestV0<Dig> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::DigestError;
     use arrow::{
         array::{
-            ArrayData, BinaryArray, BooleanArray, FixedSizeBinaryArray, Int32Array, StringArray,
+            ArrayData, ArrayRef, BinaryArray, BooleanArray, Decimal256Array, DictionaryArray,
+            DurationMicrosecondArray, DurationNanosecondArray, DurationSecondArray,
+            FixedSizeBinaryArray, Float64Array, Int8Array, Int16Array, Int32Array, Int64Array,
+            IntervalDayTimeArray, IntervalMonthDayNanoArray, IntervalYearMonthArray,
+            LargeListViewArray, ListArray, ListViewArray, NullArray, StringArray, UInt16Array,
             UInt32Array,
         },
-        buffer::Buffer,
-        datatypes::Int32Type,
+        buffer::{Buffer, OffsetBuffer},
+        datatypes::{
+            Field, Fields, Int8Type, Int32Type, IntervalDayTime, IntervalMonthDayNano, UInt16Type,
+            UnionFields, i256,
+        },
     };
     use sha3::Sha3_256;
+    use std::sync::Arc;
 
     #[test]
     fn test_ints() {
@@ -703,4 +1335,1353 @@ mod tests {
             ])),
         );
     }
+
+    #[test]
+    fn test_dictionary_array() {
+        let plain = StringArray::from(vec![Some("foo"), Some("bar"), None, Some("foo")]);
+
+        // Key width does not matter
+        let dict8: DictionaryArray<Int8Type> = vec![Some("foo"), Some("bar"), None, Some("foo")]
+            .into_iter()
+            .collect();
+        let dict32: DictionaryArray<Int32Type> = vec![Some("foo"), Some("bar"), None, Some("foo")]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&dict8),
+            ArrayDigestV0::<Sha3_256>::digest(&plain),
+        );
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&dict32),
+            ArrayDigestV0::<Sha3_256>::digest(&plain),
+        );
+
+        // Nulls in values are treated the same as nulls in keys
+        let dict_null_values = DictionaryArray::<Int32Type>::try_new(
+            Int32Array::from(vec![0, 1, 2, 0]),
+            Arc::new(StringArray::from(vec![Some("foo"), Some("bar"), None])),
+        )
+        .unwrap();
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&dict_null_values),
+            ArrayDigestV0::<Sha3_256>::digest(&plain),
+        );
+
+        // Sliced
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&dict32.slice(1, 3)),
+            ArrayDigestV0::<Sha3_256>::digest(&plain.slice(1, 3)),
+        );
+
+        // Primitive values
+        let dict_ints = DictionaryArray::<UInt16Type>::try_new(
+            UInt16Array::from(vec![Some(1), None, Some(0), Some(1)]),
+            Arc::new(Int32Array::from(vec![100, 200])),
+        )
+        .unwrap();
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&dict_ints),
+            ArrayDigestV0::<Sha3_256>::digest(&Int32Array::from(vec![
+                Some(200),
+                None,
+                Some(100),
+                Some(200)
+            ])),
+        );
+
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&dict_ints),
+            ArrayDigestV0::<Sha3_256>::digest(&dict32),
+        );
+
+        // Nested values
+        let dict_lists = DictionaryArray::<Int8Type>::try_new(
+            Int8Array::from(vec![1, 0, 1]),
+            Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+                Some(vec![Some(0), Some(1)]),
+                Some(vec![Some(2), None]),
+            ])),
+        )
+        .unwrap();
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&dict_lists),
+            ArrayDigestV0::<Sha3_256>::digest(&ListArray::from_iter_primitive::<Int32Type, _, _>(
+                vec![
+                    Some(vec![Some(2), None]),
+                    Some(vec![Some(0), Some(1)]),
+                    Some(vec![Some(2), None]),
+                ]
+            )),
+        );
+
+        // Struct values with nulls in keys, values and fields of the values
+        let struct_fields = Fields::from(vec![
+            Field::new("x", DataType::Int32, true),
+            Field::new("y", DataType::Utf8, true),
+        ]);
+        let dict_structs = DictionaryArray::<Int8Type>::try_new(
+            Int8Array::from(vec![Some(0), None, Some(1), Some(2), Some(0)]),
+            Arc::new(StructArray::new(
+                struct_fields.clone(),
+                vec![
+                    Arc::new(Int32Array::from(vec![Some(1), None, Some(3)])),
+                    Arc::new(StringArray::from(vec![None, Some("b"), Some("c")])),
+                ],
+                Some(NullBuffer::from(vec![true, true, false])),
+            )),
+        )
+        .unwrap();
+        let plain_structs = StructArray::new(
+            struct_fields,
+            vec![
+                Arc::new(Int32Array::from(vec![
+                    Some(1),
+                    None,
+                    None,
+                    Some(3),
+                    Some(1),
+                ])),
+                Arc::new(StringArray::from(vec![
+                    None,
+                    None,
+                    Some("b"),
+                    Some("c"),
+                    None,
+                ])),
+            ],
+            Some(NullBuffer::from(vec![true, false, true, false, true])),
+        );
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&dict_structs),
+            ArrayDigestV0::<Sha3_256>::digest(&plain_structs),
+        );
+
+        for split in 1..dict_structs.len() {
+            let mut digest = ArrayDigestV0::<Sha3_256>::new(dict_structs.data_type());
+            digest.update(&dict_structs.slice(0, split), None);
+            digest.update(&dict_structs.slice(split, dict_structs.len() - split), None);
+            assert_eq!(
+                digest.finalize(),
+                ArrayDigestV0::<Sha3_256>::digest(&plain_structs)
+            );
+        }
+    }
+
+    #[test]
+    fn test_run_end_encoded_array() {
+        let plain = StringArray::from(vec![
+            Some("foo"),
+            Some("foo"),
+            None,
+            None,
+            None,
+            Some("bar"),
+            Some("foo"),
+        ]);
+
+        let values = Arc::new(StringArray::from(vec![
+            Some("foo"),
+            None,
+            Some("bar"),
+            Some("foo"),
+        ]));
+
+        let ree16 =
+            RunArray::<Int16Type>::try_new(&Int16Array::from(vec![2, 5, 6, 7]), values.as_ref())
+                .unwrap();
+        let ree32 =
+            RunArray::<Int32Type>::try_new(&Int32Array::from(vec![2, 5, 6, 7]), values.as_ref())
+                .unwrap();
+        let ree64 =
+            RunArray::<Int64Type>::try_new(&Int64Array::from(vec![2, 5, 6, 7]), values.as_ref())
+                .unwrap();
+
+        for ree in [&ree16 as &dyn Array, &ree32, &ree64] {
+            assert_eq!(
+                ArrayDigestV0::<Sha3_256>::digest(ree),
+                ArrayDigestV0::<Sha3_256>::digest(&plain),
+            );
+        }
+
+        // Sliced in the middle of runs
+        for (offset, len) in [(1, 5), (3, 1), (0, 2), (4, 3), (7, 0)] {
+            assert_eq!(
+                ArrayDigestV0::<Sha3_256>::digest(&ree32.slice(offset, len)),
+                ArrayDigestV0::<Sha3_256>::digest(&plain.slice(offset, len)),
+            );
+        }
+
+        // Adjacent runs of the same value are the same as one long run
+        let merged = RunArray::<Int32Type>::try_new(
+            &Int32Array::from(vec![1, 2, 5, 6, 7]),
+            &StringArray::from(vec![
+                Some("foo"),
+                Some("foo"),
+                None,
+                Some("bar"),
+                Some("foo"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&merged),
+            ArrayDigestV0::<Sha3_256>::digest(&plain),
+        );
+
+        // Parent nulls are applied to logical positions
+        let parent_nulls = NullBuffer::from(vec![true, false, true, true, true, true, false]);
+
+        let mut d1 = ArrayDigestV0::<Sha3_256>::new(ree32.data_type());
+        d1.update(&ree32, Some(&parent_nulls));
+
+        let mut d2 = ArrayDigestV0::<Sha3_256>::new(plain.data_type());
+        d2.update(&plain, Some(&parent_nulls));
+
+        assert_eq!(d1.finalize(), d2.finalize());
+
+        // Long runs spanning several expansion chunks
+        let long_plain = Int64Array::from_iter((0..20_000).map(|i| {
+            if i / 7_000 == 1 {
+                None
+            } else {
+                Some(i / 7_000)
+            }
+        }));
+        let long_ree = RunArray::<Int32Type>::try_new(
+            &Int32Array::from(vec![7_000, 14_000, 20_000]),
+            &Int64Array::from(vec![Some(0), None, Some(2)]),
+        )
+        .unwrap();
+        let long_parent_nulls = NullBuffer::from_iter((0..20_000).map(|i| i % 3 != 0));
+
+        for (offset, len) in [(0, 20_000), (8_000, 9_000), (100, 16_384)] {
+            let mut d1 = ArrayDigestV0::<Sha3_256>::new(long_ree.data_type());
+            d1.update(
+                &long_ree.slice(offset, len),
+                Some(&long_parent_nulls.slice(offset, len)),
+            );
+
+            let mut d2 = ArrayDigestV0::<Sha3_256>::new(long_plain.data_type());
+            d2.update(
+                &long_plain.slice(offset, len),
+                Some(&long_parent_nulls.slice(offset, len)),
+            );
+
+            assert_eq!(d1.finalize(), d2.finalize());
+        }
+
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&ree32),
+            ArrayDigestV0::<Sha3_256>::digest(&StringArray::from(vec![
+                Some("foo"),
+                Some("foo"),
+                None,
+                None,
+                Some("bar"),
+                Some("bar"),
+                Some("foo"),
+            ])),
+        );
+    }
+
+    #[test]
+    fn test_struct_array() {
+        let a: ArrayRef = Arc::new(Int32Array::from(vec![1, 2, 3]));
+        let c: ArrayRef = Arc::new(StringArray::from(vec![Some("a"), None, Some("c")]));
+        let d: ArrayRef = Arc::new(Int32Array::from(vec![3, 2, 1]));
+
+        let make_struct = |b_name: &str, b_nulls: Option<Buffer>| {
+            let b_fields = vec![
+                (Arc::new(Field::new("c", DataType::Utf8, true)), c.clone()),
+                (Arc::new(Field::new("d", DataType::Int32, false)), d.clone()),
+            ];
+            let b: ArrayRef = match b_nulls {
+                None => Arc::new(StructArray::from(b_fields)),
+                Some(nulls) => Arc::new(StructArray::from((b_fields, nulls))),
+            };
+            StructArray::from(vec![
+                (Arc::new(Field::new("a", DataType::Int32, false)), a.clone()),
+                (Arc::new(Field::new(b_name, b.data_type().clone(), true)), b),
+            ])
+        };
+
+        let array = make_struct("b", None);
+
+        // Struct digest is composed of the type header and digests of the leaf columns
+        let mut expected = Sha3_256::new();
+        crate::schema_digest::hash_data_type(array.data_type(), &mut expected);
+        for leaf in [&a, &c, &d] {
+            expected.update(ArrayDigestV0::<Sha3_256>::digest(leaf.as_ref()));
+        }
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&array),
+            expected.finalize()
+        );
+
+        // Field names are part of the type
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&array),
+            ArrayDigestV0::<Sha3_256>::digest(&make_struct("bee", None)),
+        );
+
+        // All-valid bitmap is the same as no bitmap
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&array),
+            ArrayDigestV0::<Sha3_256>::digest(&make_struct("b", Some(Buffer::from([0b111])))),
+        );
+
+        // Nulls are propagated to leaf columns - a value is null when it's null in the field
+        // or in any of the parent structs
+        let array_nulls = make_struct("b", Some(Buffer::from([0b110])));
+
+        let mut expected = Sha3_256::new();
+        crate::schema_digest::hash_data_type(array_nulls.data_type(), &mut expected);
+        for leaf in [
+            a.clone(),
+            Arc::new(StringArray::from(vec![None, None, Some("c")])) as ArrayRef,
+            Arc::new(Int32Array::from(vec![None, Some(2), Some(1)])) as ArrayRef,
+        ] {
+            expected.update(ArrayDigestV0::<Sha3_256>::digest(leaf.as_ref()));
+        }
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&array_nulls),
+            expected.finalize()
+        );
+
+        // Independent of batch boundaries
+        let mut digest = ArrayDigestV0::<Sha3_256>::new(array_nulls.data_type());
+        digest.update(&array_nulls.slice(0, 1), None);
+        digest.update(&array_nulls.slice(1, 2), None);
+
+        assert_eq!(
+            digest.finalize(),
+            ArrayDigestV0::<Sha3_256>::digest(&array_nulls)
+        );
+
+        let mut digest = ArrayDigestV1::<Sha3_256>::new(array_nulls.data_type());
+        digest.update(&array_nulls.slice(0, 1), None);
+        digest.update(&array_nulls.slice(1, 2), None);
+
+        assert_eq!(
+            digest.finalize(),
+            ArrayDigestV1::<Sha3_256>::digest(&array_nulls)
+        );
+    }
+
+    #[test]
+    fn test_list_of_structs_array() {
+        let item_fields = Fields::from(vec![
+            Field::new("x", DataType::Int32, true),
+            Field::new("y", DataType::Utf8, true),
+        ]);
+        let item_field = Arc::new(Field::new_list_field(
+            DataType::Struct(item_fields.clone()),
+            true,
+        ));
+
+        let make_list = |offsets: Vec<i32>,
+                         list_nulls: Option<Vec<bool>>,
+                         xs: Vec<Option<i32>>,
+                         ys: Vec<Option<&str>>,
+                         item_nulls: Option<Vec<bool>>| {
+            let items = StructArray::new(
+                item_fields.clone(),
+                vec![
+                    Arc::new(Int32Array::from(xs)),
+                    Arc::new(StringArray::from(ys)),
+                ],
+                item_nulls.map(NullBuffer::from),
+            );
+            ListArray::new(
+                item_field.clone(),
+                OffsetBuffer::new(offsets.into()),
+                Arc::new(items),
+                list_nulls.map(NullBuffer::from),
+            )
+        };
+
+        let list = make_list(
+            vec![0, 2, 2, 3],
+            None,
+            vec![Some(1), Some(2), Some(3)],
+            vec![Some("a"), None, Some("c")],
+            None,
+        );
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&list),
+            ArrayDigestV0::<Sha3_256>::digest(&make_list(
+                vec![0, 2, 2, 3],
+                None,
+                vec![Some(1), Some(2), Some(3)],
+                vec![Some("a"), None, Some("c")],
+                None,
+            )),
+        );
+
+        // Item slides to the next list
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&list),
+            ArrayDigestV0::<Sha3_256>::digest(&make_list(
+                vec![0, 1, 1, 3],
+                None,
+                vec![Some(1), Some(2), Some(3)],
+                vec![Some("a"), None, Some("c")],
+                None,
+            )),
+        );
+
+        // Null list is different from an empty one
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&list),
+            ArrayDigestV0::<Sha3_256>::digest(&make_list(
+                vec![0, 2, 2, 3],
+                Some(vec![true, false, true]),
+                vec![Some(1), Some(2), Some(3)],
+                vec![Some("a"), None, Some("c")],
+                None,
+            )),
+        );
+
+        // Null struct items are propagated to their fields
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&make_list(
+                vec![0, 2, 2, 3],
+                None,
+                vec![Some(1), Some(2), Some(3)],
+                vec![Some("a"), None, Some("c")],
+                Some(vec![true, false, true]),
+            )),
+            ArrayDigestV0::<Sha3_256>::digest(&make_list(
+                vec![0, 2, 2, 3],
+                None,
+                vec![Some(1), None, Some(3)],
+                vec![Some("a"), None, Some("c")],
+                None,
+            )),
+        );
+
+        // Independent of batch boundaries
+        let mut digest = ArrayDigestV0::<Sha3_256>::new(list.data_type());
+        digest.update(&list.slice(0, 2), None);
+        digest.update(&list.slice(2, 1), None);
+        assert_eq!(digest.finalize(), ArrayDigestV0::<Sha3_256>::digest(&list));
+
+        // Also with nulls in lists, struct items and their fields
+        let list_nulls = make_list(
+            vec![0, 2, 2, 3, 5],
+            Some(vec![true, false, true, true]),
+            vec![Some(1), None, Some(3), Some(4), None],
+            vec![None, Some("b"), Some("c"), None, Some("e")],
+            Some(vec![false, true, true, true, false]),
+        );
+
+        for split in 1..list_nulls.len() {
+            let mut digest = ArrayDigestV0::<Sha3_256>::new(list_nulls.data_type());
+            digest.update(&list_nulls.slice(0, split), None);
+            digest.update(&list_nulls.slice(split, list_nulls.len() - split), None);
+            assert_eq!(
+                digest.finalize(),
+                ArrayDigestV0::<Sha3_256>::digest(&list_nulls)
+            );
+
+            let mut digest = ArrayDigestV1::<Sha3_256>::new(list_nulls.data_type());
+            digest.update(&list_nulls.slice(0, split), None);
+            digest.update(&list_nulls.slice(split, list_nulls.len() - split), None);
+            assert_eq!(
+                digest.finalize(),
+                ArrayDigestV1::<Sha3_256>::digest(&list_nulls)
+            );
+        }
+
+        // Struct containing a list of structs
+        let make_outer = |outer_nulls: Option<Vec<bool>>, list: ListArray| {
+            StructArray::new(
+                Fields::from(vec![Field::new("items", list.data_type().clone(), true)]),
+                vec![Arc::new(list)],
+                outer_nulls.map(NullBuffer::from),
+            )
+        };
+
+        let outer = make_outer(None, list.clone());
+
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&outer),
+            ArrayDigestV0::<Sha3_256>::digest(&make_outer(
+                None,
+                make_list(
+                    vec![0, 2, 2, 3],
+                    None,
+                    vec![Some(1), Some(2), Some(3)],
+                    vec![Some("a"), Some("b"), Some("c")],
+                    None,
+                ),
+            )),
+        );
+
+        // Outer struct nulls are propagated down to the lists
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&make_outer(
+                Some(vec![true, true, false]),
+                list.clone()
+            )),
+            ArrayDigestV0::<Sha3_256>::digest(&make_outer(
+                None,
+                make_list(
+                    vec![0, 2, 2, 3],
+                    Some(vec![true, true, false]),
+                    vec![Some(1), Some(2), Some(3)],
+                    vec![Some("a"), None, Some("c")],
+                    None,
+                ),
+            )),
+        );
+
+        // Lists of lists of structs
+        let nested = ListArray::new(
+            Arc::new(Field::new_list_field(list.data_type().clone(), true)),
+            OffsetBuffer::new(vec![0, 1, 3].into()),
+            Arc::new(list.clone()),
+            None,
+        );
+        let nested_other = ListArray::new(
+            Arc::new(Field::new_list_field(list.data_type().clone(), true)),
+            OffsetBuffer::new(vec![0, 2, 3].into()),
+            Arc::new(list.clone()),
+            None,
+        );
+
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&nested),
+            ArrayDigestV0::<Sha3_256>::digest(&nested_other),
+        );
+    }
+
+    #[test]
+    fn test_map_array() {
+        let entries_fields = Fields::from(vec![
+            Field::new("keys", DataType::Utf8, false),
+            Field::new("values", DataType::Int32, true),
+        ]);
+
+        let make_map = |offsets: Vec<i32>,
+                        nulls: Option<Vec<bool>>,
+                        keys: Vec<&str>,
+                        values: Vec<Option<i32>>,
+                        keys_sorted: bool| {
+            MapArray::new(
+                Arc::new(Field::new(
+                    "entries",
+                    DataType::Struct(entries_fields.clone()),
+                    false,
+                )),
+                OffsetBuffer::new(offsets.into()),
+                StructArray::new(
+                    entries_fields.clone(),
+                    vec![
+                        Arc::new(StringArray::from(keys)),
+                        Arc::new(Int32Array::from(values)),
+                    ],
+                    None,
+                ),
+                nulls.map(NullBuffer::from),
+                keys_sorted,
+            )
+        };
+
+        let map = make_map(
+            vec![0, 2, 2, 3, 5],
+            Some(vec![true, true, false, true]),
+            vec!["a", "b", "x", "c", "d"],
+            vec![Some(1), None, Some(100), Some(3), Some(4)],
+            false,
+        );
+
+        // Entries of null maps are ignored
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&map),
+            ArrayDigestV0::<Sha3_256>::digest(&make_map(
+                vec![0, 2, 2, 2, 4],
+                Some(vec![true, true, false, true]),
+                vec!["a", "b", "c", "d"],
+                vec![Some(1), None, Some(3), Some(4)],
+                false,
+            )),
+        );
+
+        // Null map is different from an empty one
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&map),
+            ArrayDigestV0::<Sha3_256>::digest(&make_map(
+                vec![0, 2, 2, 2, 4],
+                None,
+                vec!["a", "b", "c", "d"],
+                vec![Some(1), None, Some(3), Some(4)],
+                false,
+            )),
+        );
+
+        // Entry slides to the next map
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&map),
+            ArrayDigestV0::<Sha3_256>::digest(&make_map(
+                vec![0, 1, 2, 3, 5],
+                Some(vec![true, true, false, true]),
+                vec!["a", "b", "x", "c", "d"],
+                vec![Some(1), None, Some(100), Some(3), Some(4)],
+                false,
+            )),
+        );
+
+        // Null value
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&map),
+            ArrayDigestV0::<Sha3_256>::digest(&make_map(
+                vec![0, 2, 2, 3, 5],
+                Some(vec![true, true, false, true]),
+                vec!["a", "b", "x", "c", "d"],
+                vec![Some(1), Some(2), Some(100), Some(3), Some(4)],
+                false,
+            )),
+        );
+
+        // Sortedness of keys is part of the type
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&map),
+            ArrayDigestV0::<Sha3_256>::digest(&make_map(
+                vec![0, 2, 2, 3, 5],
+                Some(vec![true, true, false, true]),
+                vec!["a", "b", "x", "c", "d"],
+                vec![Some(1), None, Some(100), Some(3), Some(4)],
+                true,
+            )),
+        );
+
+        // Sliced
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&map.slice(1, 3)),
+            ArrayDigestV0::<Sha3_256>::digest(&make_map(
+                vec![0, 0, 0, 2],
+                Some(vec![true, false, true]),
+                vec!["c", "d"],
+                vec![Some(3), Some(4)],
+                false,
+            )),
+        );
+
+        // Independent of batch boundaries
+        let mut digest = ArrayDigestV0::<Sha3_256>::new(map.data_type());
+        digest.update(&map.slice(0, 1), None);
+        digest.update(&map.slice(1, 3), None);
+        assert_eq!(digest.finalize(), ArrayDigestV0::<Sha3_256>::digest(&map));
+    }
+
+    #[test]
+    fn test_union_array() {
+        let make_fields = |name: &str| {
+            UnionFields::try_new(
+                vec![0, 5],
+                vec![
+                    Field::new("i", DataType::Int32, true),
+                    Field::new(name, DataType::Utf8, true),
+                ],
+            )
+            .unwrap()
+        };
+
+        // [1, "a", null, 2, "b"]
+        let make_sparse = |name: &str| {
+            UnionArray::try_new(
+                make_fields(name),
+                vec![0, 5, 0, 0, 5].into(),
+                None,
+                vec![
+                    Arc::new(Int32Array::from(vec![Some(1), None, None, Some(2), None])),
+                    Arc::new(StringArray::from(vec![
+                        None,
+                        Some("a"),
+                        None,
+                        None,
+                        Some("b"),
+                    ])),
+                ],
+            )
+            .unwrap()
+        };
+
+        let sparse = make_sparse("s");
+        let dense = UnionArray::try_new(
+            make_fields("s"),
+            vec![0, 5, 0, 0, 5].into(),
+            Some(vec![0, 0, 1, 2, 1].into()),
+            vec![
+                Arc::new(Int32Array::from(vec![Some(1), None, Some(2)])),
+                Arc::new(StringArray::from(vec!["a", "b"])),
+            ],
+        )
+        .unwrap();
+
+        // Sparse and dense layouts are indistinguishable
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&sparse),
+            ArrayDigestV0::<Sha3_256>::digest(&dense),
+        );
+
+        for (offset, len) in [(1, 3), (2, 3), (4, 1)] {
+            assert_eq!(
+                ArrayDigestV0::<Sha3_256>::digest(&sparse.slice(offset, len)),
+                ArrayDigestV0::<Sha3_256>::digest(&dense.slice(offset, len)),
+            );
+        }
+
+        // Field names are part of the type
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&sparse),
+            ArrayDigestV0::<Sha3_256>::digest(&make_sparse("x")),
+        );
+
+        // Different values
+        let dense_other = UnionArray::try_new(
+            make_fields("s"),
+            vec![0, 5, 0, 0, 5].into(),
+            Some(vec![0, 0, 1, 2, 1].into()),
+            vec![
+                Arc::new(Int32Array::from(vec![Some(1), Some(0), Some(2)])),
+                Arc::new(StringArray::from(vec!["a", "b"])),
+            ],
+        )
+        .unwrap();
+
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&dense),
+            ArrayDigestV0::<Sha3_256>::digest(&dense_other),
+        );
+
+        // Different selected children
+        let dense_other = UnionArray::try_new(
+            make_fields("s"),
+            vec![0, 5, 5, 0, 0].into(),
+            Some(vec![0, 0, 1, 1, 2].into()),
+            vec![
+                Arc::new(Int32Array::from(vec![Some(1), None, Some(2)])),
+                Arc::new(StringArray::from(vec!["a", "b"])),
+            ],
+        )
+        .unwrap();
+
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&dense),
+            ArrayDigestV0::<Sha3_256>::digest(&dense_other),
+        );
+
+        // Fields are identified by position, so remapped type IDs don't affect the hash
+        let remapped = UnionArray::try_new(
+            UnionFields::try_new(
+                vec![3, 1],
+                vec![
+                    Field::new("i", DataType::Int32, true),
+                    Field::new("s", DataType::Utf8, true),
+                ],
+            )
+            .unwrap(),
+            vec![3, 1, 3, 3, 1].into(),
+            Some(vec![0, 0, 1, 2, 1].into()),
+            vec![
+                Arc::new(Int32Array::from(vec![Some(1), None, Some(2)])),
+                Arc::new(StringArray::from(vec!["a", "b"])),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&remapped),
+            ArrayDigestV0::<Sha3_256>::digest(&dense),
+        );
+        assert_eq!(
+            ArrayDigestV1::<Sha3_256>::digest(&remapped),
+            ArrayDigestV1::<Sha3_256>::digest(&dense),
+        );
+
+        // Independent of batch boundaries
+        let mut digest = ArrayDigestV0::<Sha3_256>::new(dense.data_type());
+        digest.update(&dense.slice(0, 2), None);
+        digest.update(&dense.slice(2, 3), None);
+        assert_eq!(
+            digest.finalize(),
+            ArrayDigestV0::<Sha3_256>::digest(&sparse)
+        );
+    }
+
+    #[test]
+    fn test_interval_array() {
+        let array = IntervalMonthDayNanoArray::from(vec![
+            Some(IntervalMonthDayNano::new(1, 2, 3)),
+            None,
+            Some(IntervalMonthDayNano::new(-4, 5, -6)),
+        ]);
+
+        // Values are hashed as little-endian components
+        let mut expected = Sha3_256::new();
+        crate::schema_digest::hash_data_type(array.data_type(), &mut expected);
+        expected.update(1i32.to_le_bytes());
+        expected.update(2i32.to_le_bytes());
+        expected.update(3i64.to_le_bytes());
+        expected.update([0u8]);
+        expected.update((-4i32).to_le_bytes());
+        expected.update(5i32.to_le_bytes());
+        expected.update((-6i64).to_le_bytes());
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&array),
+            expected.finalize()
+        );
+
+        let array = IntervalDayTimeArray::from(vec![
+            IntervalDayTime::new(1, 2),
+            IntervalDayTime::new(-3, 4),
+        ]);
+
+        let mut expected = Sha3_256::new();
+        crate::schema_digest::hash_data_type(array.data_type(), &mut expected);
+        expected.update(1i32.to_le_bytes());
+        expected.update(2i32.to_le_bytes());
+        expected.update((-3i32).to_le_bytes());
+        expected.update(4i32.to_le_bytes());
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&array),
+            expected.finalize()
+        );
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&array.slice(1, 1)),
+            ArrayDigestV0::<Sha3_256>::digest(&IntervalDayTimeArray::from(vec![
+                IntervalDayTime::new(-3, 4)
+            ])),
+        );
+
+        // Unit is part of the type
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&IntervalYearMonthArray::from(vec![1, 2])),
+            ArrayDigestV0::<Sha3_256>::digest(&Int32Array::from(vec![1, 2])),
+        );
+    }
+
+    #[test]
+    fn test_duration_array() {
+        let array = DurationNanosecondArray::from(vec![Some(1), None, Some(-3)]);
+
+        let mut expected = Sha3_256::new();
+        crate::schema_digest::hash_data_type(array.data_type(), &mut expected);
+        expected.update(1i64.to_le_bytes());
+        expected.update([0u8]);
+        expected.update((-3i64).to_le_bytes());
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&array),
+            expected.finalize()
+        );
+
+        // Unit is part of the type
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&DurationNanosecondArray::from(vec![1, 2])),
+            ArrayDigestV0::<Sha3_256>::digest(&DurationMicrosecondArray::from(vec![1, 2])),
+        );
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&DurationSecondArray::from(vec![1, 2])),
+            ArrayDigestV0::<Sha3_256>::digest(&Int64Array::from(vec![1, 2])),
+        );
+    }
+
+    #[test]
+    fn test_list_view_array() {
+        let list = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(0), Some(1), Some(2)]),
+            None,
+            Some(vec![Some(1), Some(2)]),
+            Some(vec![]),
+            Some(vec![Some(5), None]),
+        ]);
+
+        // Views are out of order and overlapping
+        let values = Arc::new(Int32Array::from(vec![
+            Some(5),
+            None,
+            Some(0),
+            Some(1),
+            Some(2),
+        ]));
+        let list_view = ListViewArray::new(
+            Arc::new(Field::new_list_field(DataType::Int32, true)),
+            vec![2, 0, 3, 0, 0].into(),
+            vec![3, 0, 2, 0, 2].into(),
+            values.clone(),
+            Some(NullBuffer::from(vec![true, false, true, true, true])),
+        );
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&list_view),
+            ArrayDigestV0::<Sha3_256>::digest(&list),
+        );
+
+        let large_list_view = LargeListViewArray::new(
+            Arc::new(Field::new_list_field(DataType::Int32, true)),
+            vec![2, 0, 3, 0, 0].into(),
+            vec![3, 0, 2, 0, 2].into(),
+            values.clone(),
+            Some(NullBuffer::from(vec![true, false, true, true, true])),
+        );
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&large_list_view),
+            ArrayDigestV0::<Sha3_256>::digest(&list),
+        );
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&list_view.slice(2, 3)),
+            ArrayDigestV0::<Sha3_256>::digest(&list.slice(2, 3)),
+        );
+
+        // Different window
+        let list_view_other = ListViewArray::new(
+            Arc::new(Field::new_list_field(DataType::Int32, true)),
+            vec![2, 0, 3, 0, 0].into(),
+            vec![3, 0, 1, 0, 2].into(),
+            values,
+            Some(NullBuffer::from(vec![true, false, true, true, true])),
+        );
+
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&list_view_other),
+            ArrayDigestV0::<Sha3_256>::digest(&list),
+        );
+    }
+
+    #[test]
+    fn test_null_array() {
+        let mut expected = Sha3_256::new();
+        crate::schema_digest::hash_data_type(&DataType::Null, &mut expected);
+        expected.update([0u8, 0u8, 0u8]);
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&NullArray::new(3)),
+            expected.finalize(),
+        );
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&NullArray::new(5).slice(1, 3)),
+            ArrayDigestV0::<Sha3_256>::digest(&NullArray::new(3)),
+        );
+
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&NullArray::new(3)),
+            ArrayDigestV0::<Sha3_256>::digest(&NullArray::new(4)),
+        );
+
+        // Differs from an all-null array of a concrete type only by the type header
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&NullArray::new(3)),
+            ArrayDigestV0::<Sha3_256>::digest(&Int32Array::from(vec![None, None, None])),
+        );
+    }
+
+    #[test]
+    fn test_v1_null_encoding() {
+        // Null and zero are indistinguishable in V0
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&Int8Array::from(vec![None])),
+            ArrayDigestV0::<Sha3_256>::digest(&Int8Array::from(vec![Some(0)])),
+        );
+        assert_ne!(
+            ArrayDigestV1::<Sha3_256>::digest(&Int8Array::from(vec![None])),
+            ArrayDigestV1::<Sha3_256>::digest(&Int8Array::from(vec![Some(0)])),
+        );
+
+        // Eight nulls and an empty string are indistinguishable in V0
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&StringArray::from(vec![None::<&str>; 8])),
+            ArrayDigestV0::<Sha3_256>::digest(&StringArray::from(vec![""])),
+        );
+        assert_ne!(
+            ArrayDigestV1::<Sha3_256>::digest(&StringArray::from(vec![None::<&str>; 8])),
+            ArrayDigestV1::<Sha3_256>::digest(&StringArray::from(vec![""])),
+        );
+
+        // Arrays without validity bitmap hash the same as all-valid arrays
+        assert_eq!(
+            ArrayDigestV1::<Sha3_256>::digest(&Int32Array::from(vec![1, 2, 3])),
+            ArrayDigestV1::<Sha3_256>::digest(&Int32Array::from(vec![Some(1), Some(2), Some(3)])),
+        );
+
+        // Nulls in different positions
+        assert_ne!(
+            ArrayDigestV1::<Sha3_256>::digest(&Int32Array::from(vec![Some(1), None, Some(2)])),
+            ArrayDigestV1::<Sha3_256>::digest(&Int32Array::from(vec![Some(1), Some(2), None])),
+        );
+
+        // V0 and V1 differ even when there are no nulls
+        assert_ne!(
+            ArrayDigestV0::<Sha3_256>::digest(&Int32Array::from(vec![1, 2, 3])),
+            ArrayDigestV1::<Sha3_256>::digest(&Int32Array::from(vec![1, 2, 3])),
+        );
+    }
+
+    #[test]
+    fn test_v1_batch_split() {
+        let ints = Int32Array::from(
+            (0..200)
+                .map(|i| if i % 3 == 0 { None } else { Some(i) })
+                .collect::<Vec<_>>(),
+        );
+
+        let mut digest = ArrayDigestV1::<Sha3_256>::new(&DataType::Int32);
+        digest.update(&ints.slice(0, 7), None);
+        digest.update(&ints.slice(7, 100), None);
+        digest.update(&ints.slice(107, 93), None);
+
+        assert_eq!(digest.finalize(), ArrayDigestV1::<Sha3_256>::digest(&ints));
+
+        let list = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(1), None]),
+            None,
+            Some(vec![]),
+            Some(vec![None, Some(4), Some(5)]),
+            None,
+        ]);
+
+        let mut digest = ArrayDigestV1::<Sha3_256>::new(list.data_type());
+        digest.update(&list.slice(0, 2), None);
+        digest.update(&list.slice(2, 3), None);
+
+        assert_eq!(digest.finalize(), ArrayDigestV1::<Sha3_256>::digest(&list));
+
+        // Null list and list with a null item
+        assert_ne!(
+            ArrayDigestV1::<Sha3_256>::digest(&ListArray::from_iter_primitive::<Int32Type, _, _>(
+                vec![None::<Vec<Option<i32>>>]
+            )),
+            ArrayDigestV1::<Sha3_256>::digest(&ListArray::from_iter_primitive::<Int32Type, _, _>(
+                vec![Some(vec![None])]
+            )),
+        );
+    }
+
+    #[test]
+    fn test_v1_struct_nulls() {
+        let fields = Fields::from(vec![Field::new("a", DataType::Int32, true)]);
+        let make_struct = |nulls: Option<NullBuffer>| {
+            StructArray::new(
+                fields.clone(),
+                vec![Arc::new(Int32Array::from(vec![Some(1), None]))],
+                nulls,
+            )
+        };
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&make_struct(None)),
+            ArrayDigestV0::<Sha3_256>::digest(&make_struct(Some(NullBuffer::from(vec![
+                true, false
+            ])))),
+        );
+        assert_ne!(
+            ArrayDigestV1::<Sha3_256>::digest(&make_struct(None)),
+            ArrayDigestV1::<Sha3_256>::digest(&make_struct(Some(NullBuffer::from(vec![
+                true, false
+            ])))),
+        );
+
+        // Lists of structs
+        let items = make_struct(Some(NullBuffer::from(vec![true, false])));
+        let list = ListArray::new(
+            Arc::new(Field::new_list_field(items.data_type().clone(), true)),
+            OffsetBuffer::new(vec![0, 1, 2, 2].into()),
+            Arc::new(items),
+            None,
+        );
+
+        let mut digest = ArrayDigestV1::<Sha3_256>::new(list.data_type());
+        digest.update(&list.slice(0, 1), None);
+        digest.update(&list.slice(1, 2), None);
+
+        assert_eq!(digest.finalize(), ArrayDigestV1::<Sha3_256>::digest(&list));
+    }
+
+    #[test]
+    fn test_v1_bool_array() {
+        let values: Vec<Option<bool>> = (0..1000)
+            .map(|i| match i % 7 {
+                0 => None,
+                n => Some(n % 2 == 0 || i % 5 == 0),
+            })
+            .collect();
+        let array = BooleanArray::from(values.clone());
+
+        // Same as V0 when it comes to values
+        assert_eq!(
+            ArrayDigestV1::<Sha3_256>::digest(&BooleanArray::from(vec![true, false, true])),
+            ArrayDigestV1::<Sha3_256>::digest(&BooleanArray::from(vec![
+                Some(true),
+                Some(false),
+                Some(true)
+            ])),
+        );
+        assert_ne!(
+            ArrayDigestV1::<Sha3_256>::digest(&BooleanArray::from(vec![true, false, true])),
+            ArrayDigestV1::<Sha3_256>::digest(&BooleanArray::from(vec![true, true, true])),
+        );
+        assert_ne!(
+            ArrayDigestV1::<Sha3_256>::digest(&BooleanArray::from(vec![Some(false)])),
+            ArrayDigestV1::<Sha3_256>::digest(&BooleanArray::from(vec![None])),
+        );
+        assert_ne!(
+            ArrayDigestV1::<Sha3_256>::digest(&BooleanArray::from(vec![false; 8])),
+            ArrayDigestV1::<Sha3_256>::digest(&BooleanArray::from(vec![false; 9])),
+        );
+
+        // Values in null slots are ignored
+        assert_eq!(
+            ArrayDigestV1::<Sha3_256>::digest(&BooleanArray::new(
+                vec![true, true].into(),
+                Some(NullBuffer::from(vec![true, false]))
+            )),
+            ArrayDigestV1::<Sha3_256>::digest(&BooleanArray::from(vec![Some(true), None])),
+        );
+
+        // Unaligned slices and batch boundaries
+        for (offset, len) in [(0, 1000), (3, 500), (13, 987), (64, 129)] {
+            assert_eq!(
+                ArrayDigestV1::<Sha3_256>::digest(&array.slice(offset, len)),
+                ArrayDigestV1::<Sha3_256>::digest(&BooleanArray::from(
+                    values[offset..offset + len].to_vec()
+                )),
+            );
+        }
+
+        let mut digest = ArrayDigestV1::<Sha3_256>::new(&DataType::Boolean);
+        for (offset, len) in [(0, 1), (1, 70), (71, 3), (74, 900), (974, 26)] {
+            digest.update(&array.slice(offset, len), None);
+        }
+        assert_eq!(digest.finalize(), ArrayDigestV1::<Sha3_256>::digest(&array));
+
+        // Lists of booleans
+        let list = ListArray::new(
+            Arc::new(Field::new_list_field(DataType::Boolean, true)),
+            OffsetBuffer::from_lengths([3, 0, 10, 987]),
+            Arc::new(array.clone()),
+            Some(NullBuffer::from(vec![true, false, true, true])),
+        );
+
+        let mut digest = ArrayDigestV1::<Sha3_256>::new(list.data_type());
+        digest.update(&list.slice(0, 3), None);
+        digest.update(&list.slice(3, 1), None);
+        assert_eq!(digest.finalize(), ArrayDigestV1::<Sha3_256>::digest(&list));
+    }
+
+    #[test]
+    fn test_fixed_size_little_endian() {
+        fn expected(data_type: &DataType, bytes: &[&[u8]]) -> Output<Sha3_256> {
+            let mut hasher = Sha3_256::new();
+            crate::schema_digest::hash_data_type(data_type, &mut hasher);
+            for b in bytes {
+                hasher.update(b);
+            }
+            hasher.finalize()
+        }
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&Int32Array::from(vec![1, -2])),
+            expected(
+                &DataType::Int32,
+                &[&1i32.to_le_bytes(), &(-2i32).to_le_bytes()]
+            ),
+        );
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&UInt16Array::from(vec![Some(0x0102), None])),
+            expected(&DataType::UInt16, &[&[0x02, 0x01], &[0]]),
+        );
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(&Float64Array::from(vec![1.5])),
+            expected(&DataType::Float64, &[&1.5f64.to_le_bytes()]),
+        );
+
+        let value = i256::from_parts(0x0102_0304, 0x0506);
+        let mut value_bytes = Vec::new();
+        value_bytes.extend_from_slice(&0x0102_0304u128.to_le_bytes());
+        value_bytes.extend_from_slice(&0x0506i128.to_le_bytes());
+        assert_eq!(value.to_le_bytes().as_slice(), value_bytes.as_slice());
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::digest(
+                &Decimal256Array::from(vec![value])
+                    .with_precision_and_scale(76, 0)
+                    .unwrap()
+            ),
+            expected(&DataType::Decimal256(76, 0), &[&value_bytes]),
+        );
+    }
+
+    #[test]
+    fn test_to_little_endian() {
+        // Buffers as they are laid out in memory of a big-endian host
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&0x0102_0304i32.to_be_bytes());
+        buf.extend_from_slice(&(-2i32).to_be_bytes());
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&0x0102_0304i32.to_le_bytes());
+        expected.extend_from_slice(&(-2i32).to_le_bytes());
+        assert_eq!(crate::utils::swap_byte_order(&buf, 4), expected);
+
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&0x0102_0304u128.to_be_bytes());
+        buf.extend_from_slice(&0x0506i128.to_be_bytes());
+        assert_eq!(
+            crate::utils::swap_byte_order(&buf, 32),
+            i256::from_parts(0x0102_0304, 0x0506).to_le_bytes(),
+        );
+
+        assert_eq!(crate::utils::swap_byte_order(&[1, 2, 3], 1), [1, 2, 3]);
+
+        // Native buffers are converted on any host
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&0x0102u16.to_ne_bytes());
+        buf.extend_from_slice(&0x0304u16.to_ne_bytes());
+        assert_eq!(
+            crate::utils::to_little_endian(&buf, 2).as_ref(),
+            [0x02, 0x01, 0x04, 0x03],
+        );
+    }
+
+    #[test]
+    fn test_try_digest() {
+        let array = Int32Array::from(vec![Some(1), None, Some(3)]);
+
+        assert_eq!(
+            ArrayDigestV0::<Sha3_256>::try_digest(&array).unwrap(),
+            ArrayDigestV0::<Sha3_256>::digest(&array),
+        );
+        assert_eq!(
+            ArrayDigestV1::<Sha3_256>::try_digest(&array).unwrap(),
+            ArrayDigestV1::<Sha3_256>::digest(&array),
+        );
+
+        let bad_ree = DataType::RunEndEncoded(
+            Arc::new(Field::new("run_ends", DataType::Int8, false)),
+            Arc::new(Field::new("values", DataType::Utf8, true)),
+        );
+        assert!(matches!(
+            ArrayDigestV0::<Sha3_256>::try_new(&bad_ree),
+            Err(DigestError::UnsupportedType { .. })
+        ));
+
+        // Rejected before creating digests of keys and values, which would panic
+        let bad_map = DataType::Map(
+            Arc::new(Field::new("entries", DataType::Utf8, false)),
+            false,
+        );
+        assert!(matches!(
+            ArrayDigestV0::<Sha3_256>::try_new(&bad_map),
+            Err(DigestError::UnsupportedType { .. })
+        ));
+        assert!(matches!(
+            ArrayDigestV1::<Sha3_256>::try_new(&bad_map),
+            Err(DigestError::UnsupportedType { .. })
+        ));
+
+        let mut digest = ArrayDigestV0::<Sha3_256>::try_new(&DataType::Int32).unwrap();
+        assert_eq!(
+            digest.try_update(&array, Some(&NullBuffer::new_valid(2))),
+            Err(DigestError::NullBitmapLengthMismatch {
+                expected: 3,
+                actual: 2
+            }),
+        );
+
+        // Arrays of a different type are rejected instead of panicking
+        let struct_type =
+            DataType::Struct(Fields::from(vec![Field::new("a", DataType::Int32, true)]));
+        let union_type = DataType::Union(
+            UnionFields::try_new(vec![0], vec![Field::new("a", DataType::Int32, true)]).unwrap(),
+            arrow::datatypes::UnionMode::Sparse,
+        );
+        let map_type = DataType::Map(
+            Arc::new(Field::new(
+                "entries",
+                DataType::Struct(Fields::from(vec![
+                    Field::new("keys", DataType::Utf8, false),
+                    Field::new("values", DataType::Int32, true),
+                ])),
+                false,
+            )),
+            false,
+        );
+
+        for data_type in [struct_type, union_type, map_type] {
+            let mut digest = ArrayDigestV1::<Sha3_256>::try_new(&data_type).unwrap();
+            assert_eq!(
+                digest.try_update(&array, None),
+                Err(DigestError::TypeMismatch {
+                    expected: data_type.clone(),
+                    actual: DataType::Int32,
+                }),
+            );
+        }
+
+        // Logically equivalent types are accepted
+        let mut digest = ArrayDigestV0::<Sha3_256>::try_new(&DataType::Utf8).unwrap();
+        digest
+            .try_update(&LargeStringArray::from(vec!["a"]), None)
+            .unwrap();
+        assert_eq!(
+            digest.finalize(),
+            ArrayDigestV0::<Sha3_256>::digest(&StringArray::from(vec!["a"])),
+        );
+
+        // V0 flattens nested structs, so only top-level structs may be encoded differently
+        let inner = StructArray::from(vec![(
+            Arc::new(Field::new("a", DataType::Int32, true)),
+            Arc::new(Int32Array::from(vec![1, 2])) as ArrayRef,
+        )]);
+        let inner_dict: ArrayRef = Arc::new(
+            DictionaryArray::<Int8Type>::try_new(
+                Int8Array::from(vec![0, 1]),
+                Arc::new(inner.clone()),
+            )
+            .unwrap(),
+        );
+        let make_outer = |b: ArrayRef| {
+            StructArray::from(vec![(
+                Arc::new(Field::new("b", b.data_type().clone(), true)),
+                b,
+            )])
+        };
+        let outer = make_outer(Arc::new(inner.clone()));
+        let outer_dict = make_outer(inner_dict.clone());
+
+        let mut digest = ArrayDigestV0::<Sha3_256>::try_new(inner.data_type()).unwrap();
+        digest.try_update(inner_dict.as_ref(), None).unwrap();
+        assert_eq!(digest.finalize(), ArrayDigestV0::<Sha3_256>::digest(&inner));
+
+        let mut digest = ArrayDigestV0::<Sha3_256>::try_new(outer.data_type()).unwrap();
+        assert_eq!(
+            digest.try_update(&outer_dict, None),
+            Err(DigestError::TypeMismatch {
+                expected: outer.data_type().clone(),
+                actual: outer_dict.data_type().clone(),
+            }),
+        );
+
+        let mut digest = ArrayDigestV1::<Sha3_256>::try_new(outer.data_type()).unwrap();
+        digest.try_update(&outer_dict, None).unwrap();
+        assert_eq!(digest.finalize(), ArrayDigestV1::<Sha3_256>::digest(&outer));
+    }
 }
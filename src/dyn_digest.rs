@@ -0,0 +1,526 @@
+use crate::array_digest::{ArrayDigestImpl, DigestVersion};
+use crate::hasher::DynHasher;
+use crate::record_digest::RecordDigestImpl;
+use crate::{DigestError, RecordDigestOptions};
+use arrow::{
+    array::Array,
+    buffer::NullBuffer,
+    datatypes::{DataType, Schema},
+    record_batch::RecordBatch,
+};
+use digest::DynDigest;
+use std::str::FromStr;
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+/// Hash algorithms that can be selected at runtime by name or by
+/// [multihash](https://github.com/multiformats/multicodec/blob/master/table.csv) code.
+/// Every algorithm is available only when the crate feature of the same name is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HashAlgorithm {
+    #[cfg(feature = "sha2")]
+    Sha2_256,
+    #[cfg(feature = "sha3")]
+    Sha3_256,
+    #[cfg(feature = "blake2")]
+    Blake2b256,
+    #[cfg(feature = "blake2")]
+    Blake2b512,
+    #[cfg(feature = "blake3")]
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// All algorithms enabled in this build
+    pub const ALL: &[HashAlgorithm] = &[
+        #[cfg(feature = "sha2")]
+        HashAlgorithm::Sha2_256,
+        #[cfg(feature = "sha3")]
+        HashAlgorithm::Sha3_256,
+        #[cfg(feature = "blake2")]
+        HashAlgorithm::Blake2b256,
+        #[cfg(feature = "blake2")]
+        HashAlgorithm::Blake2b512,
+        #[cfg(feature = "blake3")]
+        HashAlgorithm::Blake3,
+    ];
+
+    /// Name of the algorithm in the multicodec table, e.g. `sha3-256`
+    pub fn name(&self) -> &'static str {
+        match *self {
+            #[cfg(feature = "sha2")]
+            HashAlgorithm::Sha2_256 => "sha2-256",
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_256 => "sha3-256",
+            #[cfg(feature = "blake2")]
+            HashAlgorithm::Blake2b256 => "blake2b-256",
+            #[cfg(feature = "blake2")]
+            HashAlgorithm::Blake2b512 => "blake2b-512",
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+
+    /// Multihash code of the algorithm
+    pub fn code(&self) -> u64 {
+        match *self {
+            #[cfg(feature = "sha2")]
+            HashAlgorithm::Sha2_256 => 0x12,
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_256 => 0x16,
+            #[cfg(feature = "blake2")]
+            HashAlgorithm::Blake2b256 => 0xb220,
+            #[cfg(feature = "blake2")]
+            HashAlgorithm::Blake2b512 => 0xb240,
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => 0x1e,
+        }
+    }
+
+    /// Finds the algorithm by its multicodec name. `blake2b` is accepted as an alias of
+    /// `blake2b-512`.
+    pub fn from_name(name: &str) -> Result<Self, DigestError> {
+        #[cfg(feature = "blake2")]
+        if name == "blake2b" {
+            return Ok(HashAlgorithm::Blake2b512);
+        }
+        Self::ALL
+            .iter()
+            .find(|a| a.name() == name)
+            .copied()
+            .ok_or_else(|| DigestError::UnsupportedAlgorithm {
+                algorithm: name.to_string(),
+            })
+    }
+
+    /// Finds the algorithm by its multihash code
+    pub fn from_code(code: u64) -> Result<Self, DigestError> {
+        Self::ALL
+            .iter()
+            .find(|a| a.code() == code)
+            .copied()
+            .ok_or_else(|| DigestError::UnsupportedAlgorithm {
+                algorithm: format!("{code:#x}"),
+            })
+    }
+
+    /// Size of the digest in bytes
+    pub fn output_size(&self) -> usize {
+        self.new_hasher().output_size()
+    }
+
+    /// Creates a hasher of this algorithm in its initial state
+    pub fn new_hasher(&self) -> Box<dyn DynDigest> {
+        match *self {
+            #[cfg(feature = "sha2")]
+            HashAlgorithm::Sha2_256 => Box::new(sha2::Sha256::default()),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_256 => Box::new(sha3::Sha3_256::default()),
+            #[cfg(feature = "blake2")]
+            HashAlgorithm::Blake2b256 => {
+                Box::new(blake2::Blake2b::<digest::consts::U32>::default())
+            }
+            #[cfg(feature = "blake2")]
+            HashAlgorithm::Blake2b512 => Box::new(blake2::Blake2b512::default()),
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => Box::new(crate::Blake3::default()),
+        }
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = DigestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_name(s)
+    }
+}
+
+impl std::fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+// Type-erased digests are identical for all versions except for the version itself, so
+// they are generated by macros
+macro_rules! dyn_array_digest {
+    ($name:ident, $version:ident, $generic:literal) => {
+        #[doc = concat!(
+            "Same as [`crate::", $generic, "`] but uses a hash algorithm selected at runtime. ",
+            "The hasher is reset before use - nested digests are created by cloning and ",
+            "resetting it too."
+        )]
+        pub struct $name(ArrayDigestImpl<DynHasher>);
+
+        impl $name {
+            pub fn digest(hasher: Box<dyn DynDigest>, array: &dyn Array) -> Box<[u8]> {
+                let mut d = Self::new(hasher, array.data_type());
+                d.update(array, None);
+                d.finalize()
+            }
+
+            #[doc = concat!(
+                "Same as [`", stringify!($name), "::digest`] but returns an error instead of ",
+                "panicking"
+            )]
+            pub fn try_digest(
+                hasher: Box<dyn DynDigest>,
+                array: &dyn Array,
+            ) -> Result<Box<[u8]>, DigestError> {
+                let mut d = Self::try_new(hasher, array.data_type())?;
+                d.try_update(array, None)?;
+                Ok(d.finalize())
+            }
+
+            pub fn new(hasher: Box<dyn DynDigest>, data_type: &DataType) -> Self {
+                Self(ArrayDigestImpl::new(
+                    DigestVersion::$version,
+                    data_type,
+                    DynHasher::new(hasher),
+                ))
+            }
+
+            #[doc = concat!(
+                "Same as [`", stringify!($name), "::new`] but returns an error instead of ",
+                "panicking"
+            )]
+            pub fn try_new(
+                hasher: Box<dyn DynDigest>,
+                data_type: &DataType,
+            ) -> Result<Self, DigestError> {
+                crate::check_data_type(data_type)?;
+                Ok(Self::new(hasher, data_type))
+            }
+
+            pub fn update(&mut self, array: &dyn Array, parent_null_bitmap: Option<&NullBuffer>) {
+                self.0.update(array, parent_null_bitmap);
+            }
+
+            #[doc = concat!(
+                "Same as [`", stringify!($name), "::update`] but returns an error instead of ",
+                "panicking"
+            )]
+            pub fn try_update(
+                &mut self,
+                array: &dyn Array,
+                parent_null_bitmap: Option<&NullBuffer>,
+            ) -> Result<(), DigestError> {
+                self.0.try_update(array, parent_null_bitmap)
+            }
+
+            pub fn finalize(self) -> Box<[u8]> {
+                self.0.finalize()
+            }
+        }
+    };
+}
+
+macro_rules! dyn_record_digest {
+    ($name:ident, $version:ident, $generic:literal) => {
+        #[doc = concat!(
+            "Same as [`crate::", $generic, "`] but uses a hash algorithm selected at runtime. ",
+            "The hasher is reset before use - nested digests are created by cloning and ",
+            "resetting it too."
+        )]
+        pub struct $name(RecordDigestImpl<DynHasher>);
+
+        impl $name {
+            pub fn digest(hasher: Box<dyn DynDigest>, batch: &RecordBatch) -> Box<[u8]> {
+                let mut d = Self::new(hasher, batch.schema().as_ref());
+                d.update(batch);
+                d.finalize()
+            }
+
+            #[doc = concat!(
+                "Same as [`", stringify!($name), "::digest`] but returns an error instead of ",
+                "panicking"
+            )]
+            pub fn try_digest(
+                hasher: Box<dyn DynDigest>,
+                batch: &RecordBatch,
+            ) -> Result<Box<[u8]>, DigestError> {
+                let mut d = Self::try_new(hasher, batch.schema().as_ref())?;
+                d.try_update(batch)?;
+                Ok(d.finalize())
+            }
+
+            pub fn new(hasher: Box<dyn DynDigest>, schema: &Schema) -> Self {
+                Self::new_with_options(hasher, schema, &RecordDigestOptions::default())
+            }
+
+            #[doc = concat!(
+                "Same as [`", stringify!($name), "::new`] but returns an error instead of ",
+                "panicking"
+            )]
+            pub fn try_new(
+                hasher: Box<dyn DynDigest>,
+                schema: &Schema,
+            ) -> Result<Self, DigestError> {
+                Self::try_new_with_options(hasher, schema, &RecordDigestOptions::default())
+            }
+
+            pub fn new_with_options(
+                hasher: Box<dyn DynDigest>,
+                schema: &Schema,
+                options: &RecordDigestOptions,
+            ) -> Self {
+                Self(RecordDigestImpl::new(
+                    DigestVersion::$version,
+                    schema,
+                    options,
+                    DynHasher::new(hasher),
+                ))
+            }
+
+            #[doc = concat!(
+                "Same as [`", stringify!($name), "::new_with_options`] but returns an error ",
+                "instead of panicking"
+            )]
+            pub fn try_new_with_options(
+                hasher: Box<dyn DynDigest>,
+                schema: &Schema,
+                options: &RecordDigestOptions,
+            ) -> Result<Self, DigestError> {
+                crate::check_schema(schema)?;
+                Ok(Self::new_with_options(hasher, schema, options))
+            }
+
+            pub fn update(&mut self, batch: &RecordBatch) {
+                self.0.update(batch);
+            }
+
+            #[doc = concat!(
+                "Same as [`", stringify!($name), "::update`] but returns an error instead of ",
+                "panicking"
+            )]
+            pub fn try_update(&mut self, batch: &RecordBatch) -> Result<(), DigestError> {
+                self.0.try_update(batch)
+            }
+
+            pub fn finalize(self) -> Box<[u8]> {
+                self.0.finalize()
+            }
+        }
+    };
+}
+
+dyn_array_digest!(DynArrayDigestV0, V0, "ArrayDigestV0");
+dyn_array_digest!(DynArrayDigestV1, V1, "ArrayDigestV1");
+dyn_record_digest!(DynRecordDigestV0, V0, "RecordDigestV0");
+dyn_record_digest!(DynRecordDigestV1, V1, "RecordDigestV1");
+
+/////////////////////////////////////////////////////////////////////////////////////////
+// Tests
+/////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_batch;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::Field;
+    use std::sync::Arc;
+
+    #[cfg(any(
+        feature = "sha2",
+        feature = "sha3",
+        feature = "blake2",
+        feature = "blake3"
+    ))]
+    fn assert_same<Dig: digest::Digest>(algorithm: HashAlgorithm) {
+        use crate::{
+            ArrayDigest, ArrayDigestV0, ArrayDigestV1, RecordDigest, RecordDigestV0, RecordDigestV1,
+        };
+
+        let batch = test_batch();
+
+        for column in batch.columns() {
+            let array = column.as_ref();
+            assert_eq!(
+                &DynArrayDigestV0::digest(algorithm.new_hasher(), array)[..],
+                &ArrayDigestV0::<Dig>::digest(array)[..],
+            );
+            assert_eq!(
+                &DynArrayDigestV1::digest(algorithm.new_hasher(), array)[..],
+                &ArrayDigestV1::<Dig>::digest(array)[..],
+            );
+        }
+
+        assert_eq!(
+            &DynRecordDigestV0::digest(algorithm.new_hasher(), &batch)[..],
+            &RecordDigestV0::<Dig>::digest(&batch)[..],
+        );
+        assert_eq!(
+            &DynRecordDigestV1::digest(algorithm.new_hasher(), &batch)[..],
+            &RecordDigestV1::<Dig>::digest(&batch)[..],
+        );
+        assert_eq!(
+            algorithm.output_size(),
+            <Dig as digest::Digest>::output_size()
+        );
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn test_dyn_digest_sha2() {
+        assert_same::<sha2::Sha256>(HashAlgorithm::Sha2_256);
+    }
+
+    #[cfg(feature = "sha3")]
+    #[test]
+    fn test_dyn_digest_sha3() {
+        assert_same::<sha3::Sha3_256>(HashAlgorithm::Sha3_256);
+    }
+
+    #[cfg(feature = "blake2")]
+    #[test]
+    fn test_dyn_digest_blake2() {
+        assert_same::<blake2::Blake2b<digest::consts::U32>>(HashAlgorithm::Blake2b256);
+        assert_same::<blake2::Blake2b512>(HashAlgorithm::Blake2b512);
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_dyn_digest_blake3() {
+        assert_same::<crate::Blake3>(HashAlgorithm::Blake3);
+    }
+
+    #[test]
+    fn test_dyn_digest_incremental() {
+        let batch = test_batch();
+
+        for algorithm in HashAlgorithm::ALL {
+            let mut digest = DynRecordDigestV1::new(algorithm.new_hasher(), &batch.schema());
+            digest.update(&batch.slice(0, 1));
+            digest.try_update(&batch.slice(1, 2)).unwrap();
+            assert_eq!(
+                digest.finalize(),
+                DynRecordDigestV1::digest(algorithm.new_hasher(), &batch),
+            );
+
+            let array = batch.column(3).as_ref();
+            let mut digest = DynArrayDigestV1::new(algorithm.new_hasher(), array.data_type());
+            digest.update(&array.slice(0, 2), None);
+            digest.try_update(&array.slice(2, 1), None).unwrap();
+            assert_eq!(
+                digest.finalize(),
+                DynArrayDigestV1::digest(algorithm.new_hasher(), array),
+            );
+        }
+    }
+
+    #[test]
+    fn test_dyn_digest_resets_hasher() {
+        let batch = test_batch();
+
+        for algorithm in HashAlgorithm::ALL {
+            let mut hasher = algorithm.new_hasher();
+            hasher.update(b"garbage");
+            assert_eq!(
+                DynRecordDigestV1::digest(hasher, &batch),
+                DynRecordDigestV1::digest(algorithm.new_hasher(), &batch),
+            );
+
+            let mut hasher = algorithm.new_hasher();
+            hasher.update(b"garbage");
+            let array = batch.column(0).as_ref();
+            assert_eq!(
+                DynArrayDigestV0::digest(hasher, array),
+                DynArrayDigestV0::digest(algorithm.new_hasher(), array),
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash_algorithm_lookup() {
+        for algorithm in HashAlgorithm::ALL {
+            assert_eq!(HashAlgorithm::from_name(algorithm.name()), Ok(*algorithm));
+            assert_eq!(HashAlgorithm::from_code(algorithm.code()), Ok(*algorithm));
+            assert_eq!(algorithm.to_string().parse(), Ok(*algorithm));
+        }
+
+        #[cfg(feature = "blake2")]
+        assert_eq!(
+            "blake2b".parse::<HashAlgorithm>(),
+            Ok(HashAlgorithm::Blake2b512)
+        );
+
+        assert_eq!(
+            HashAlgorithm::from_name("md5"),
+            Err(DigestError::UnsupportedAlgorithm {
+                algorithm: "md5".to_string()
+            }),
+        );
+        assert_eq!(
+            HashAlgorithm::from_code(0xd5).unwrap_err().to_string(),
+            "Hash algorithm '0xd5' is not supported",
+        );
+    }
+
+    #[test]
+    fn test_dyn_digest_errors() {
+        let array = Int32Array::from(vec![1, 2, 3]);
+        let batch = test_batch();
+
+        let bad_map = DataType::Map(
+            Arc::new(Field::new("entries", DataType::Utf8, false)),
+            false,
+        );
+        let bad_schema = Schema::new(vec![Field::new("m", bad_map.clone(), true)]);
+        let unsupported = |path: &str| DigestError::UnsupportedType {
+            data_type: bad_map.clone(),
+            path: path.to_string(),
+        };
+
+        for algorithm in HashAlgorithm::ALL {
+            assert_eq!(
+                &DynArrayDigestV0::try_digest(algorithm.new_hasher(), &array).unwrap()[..],
+                &DynArrayDigestV0::digest(algorithm.new_hasher(), &array)[..],
+            );
+            assert_eq!(
+                &DynRecordDigestV1::try_digest(algorithm.new_hasher(), &batch).unwrap()[..],
+                &DynRecordDigestV1::digest(algorithm.new_hasher(), &batch)[..],
+            );
+
+            assert_eq!(
+                DynArrayDigestV1::try_new(algorithm.new_hasher(), &bad_map).err(),
+                Some(unsupported("")),
+            );
+            assert_eq!(
+                DynRecordDigestV0::try_new(algorithm.new_hasher(), &bad_schema).err(),
+                Some(unsupported("m")),
+            );
+            assert_eq!(
+                DynRecordDigestV1::try_new_with_options(
+                    algorithm.new_hasher(),
+                    &bad_schema,
+                    &RecordDigestOptions::new().with_row_count(true),
+                )
+                .err(),
+                Some(unsupported("m")),
+            );
+
+            let mut digest = DynArrayDigestV0::new(algorithm.new_hasher(), array.data_type());
+            assert_eq!(
+                digest.try_update(&array, Some(&NullBuffer::new_null(2))),
+                Err(DigestError::NullBitmapLengthMismatch {
+                    expected: 3,
+                    actual: 2
+                }),
+            );
+
+            let mut digest = DynArrayDigestV1::new(algorithm.new_hasher(), &DataType::Utf8);
+            assert_eq!(
+                digest.try_update(&array, None),
+                Err(DigestError::TypeMismatch {
+                    expected: DataType::Utf8,
+                    actual: DataType::Int32,
+                }),
+            );
+        }
+    }
+}
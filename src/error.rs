@@ -0,0 +1,86 @@
+use arrow::datatypes::DataType;
+use std::fmt;
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum DigestError {
+    /// Data type can't be hashed. `path` is a dot-separated path of the field where the type
+    /// was found, which is empty when it is the top-level type of an array.
+    UnsupportedType { data_type: DataType, path: String },
+    /// Array has a type that doesn't match the type the digest was created with
+    TypeMismatch {
+        expected: DataType,
+        actual: DataType,
+    },
+    /// Length of the parent null bitmap doesn't match the length of the array
+    NullBitmapLengthMismatch { expected: usize, actual: usize },
+    /// Batch doesn't match the schema the digest was created with. Types are `None` when
+    /// the field is missing in the schema or in the batch.
+    SchemaMismatch {
+        path: String,
+        expected: Option<DataType>,
+        actual: Option<DataType>,
+    },
+    /// Hash algorithm name or multihash code is unknown or its feature is not enabled
+    UnsupportedAlgorithm { algorithm: String },
+    /// IPC metadata declares a byte order different from the native byte order of the host
+    NonNativeEndianness { endianness: String },
+}
+
+impl fmt::Display for DigestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DigestError::UnsupportedType { data_type, path } if path.is_empty() => {
+                write!(f, "Type {data_type} is not supported")
+            }
+            DigestError::UnsupportedType { data_type, path } => {
+                write!(f, "Type {data_type} of field '{path}' is not supported")
+            }
+            DigestError::TypeMismatch { expected, actual } => write!(
+                f,
+                "Array has type {actual} that doesn't match type {expected} of the digest"
+            ),
+            DigestError::NullBitmapLengthMismatch { expected, actual } => write!(
+                f,
+                "Null bitmap has length {actual} while array has length {expected}"
+            ),
+            DigestError::SchemaMismatch {
+                path,
+                expected: Some(expected),
+                actual: Some(actual),
+            } => write!(
+                f,
+                "Field '{path}' has type {actual} that doesn't match type {expected} of the schema"
+            ),
+            DigestError::SchemaMismatch {
+                path,
+                expected: Some(_),
+                actual: None,
+            } => write!(f, "Field '{path}' of the schema is missing in the batch"),
+            DigestError::SchemaMismatch {
+                path,
+                expected: None,
+                actual: Some(_),
+            } => write!(f, "Field '{path}' is not part of the schema"),
+            DigestError::SchemaMismatch { path, .. } => {
+                write!(f, "Field '{path}' doesn't match the schema")
+            }
+            DigestError::UnsupportedAlgorithm { algorithm } => {
+                write!(f, "Hash algorithm '{algorithm}' is not supported")
+            }
+            DigestError::NonNativeEndianness { endianness } => write!(
+                f,
+                "Data in {endianness} byte order can't be hashed on a {}-endian host",
+                if cfg!(target_endian = "little") {
+                    "little"
+                } else {
+                    "big"
+                }
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DigestError {}
@@ -0,0 +1,67 @@
+use digest::{Digest, DynDigest, Output};
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+// Hash function that drives the canonical encoding. Digests of nested arrays, validity and
+// boolean bitmaps are computed by separate hashers created via `fork()` and then fed into
+// the parent hasher, so an implementation only needs to know how to make a fresh instance
+// of the same algorithm.
+pub(crate) trait HashSink {
+    type Output: AsRef<[u8]>;
+
+    // Creates a hasher of the same algorithm in its initial state
+    fn fork(&self) -> Self;
+
+    fn update(&mut self, data: impl AsRef<[u8]>);
+
+    fn finalize(self) -> Self::Output;
+}
+
+impl<Dig: Digest> HashSink for Dig {
+    type Output = Output<Dig>;
+
+    fn fork(&self) -> Self {
+        Dig::new()
+    }
+
+    #[inline]
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        Digest::update(self, data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        Digest::finalize(self)
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+// Type-erased hasher selected at runtime
+pub(crate) struct DynHasher(Box<dyn DynDigest>);
+
+impl DynHasher {
+    // Resets the hasher, so that data it was fed before doesn't affect the result
+    pub fn new(mut hasher: Box<dyn DynDigest>) -> Self {
+        hasher.reset();
+        Self(hasher)
+    }
+}
+
+impl HashSink for DynHasher {
+    type Output = Box<[u8]>;
+
+    fn fork(&self) -> Self {
+        let mut hasher = self.0.box_clone();
+        hasher.reset();
+        Self(hasher)
+    }
+
+    #[inline]
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.0.update(data.as_ref());
+    }
+
+    fn finalize(self) -> Self::Output {
+        self.0.finalize()
+    }
+}
@@ -1,9 +1,45 @@
+//! Stable hashes for Apache Arrow arrays and record batches. See the README for the details
+//! of the encoding.
+//!
+//! # Nested digests
+//! Digests of nested arrays (e.g. fields of structs, items of lists and validity bitmaps) are
+//! produced by clones of the hasher and fed into the digest of their parent. Adapters with
+//! the output size chosen by the caller ([`Xof`], [`VarOutput`]) keep digests of nested
+//! arrays at least 32 bytes long, so short digests are as strong as their length allows.
+//!
+//! # Non-cryptographic hashes
+//! `Xxh3_64`, `Xxh3_128` (feature `xxh3`) and `StdHasher` (feature `std-hasher`) are fast,
+//! but provide no collision resistance against adversarial inputs. Use them only for
+//! in-process equality checks and caching.
+
+mod adapters;
 mod array_digest;
+mod dyn_digest;
+mod error;
+mod hasher;
 mod record_digest;
 mod schema_digest;
+#[cfg(test)]
+mod test_utils;
 mod traits;
 mod utils;
 
-pub use array_digest::ArrayDigestV0;
-pub use record_digest::RecordDigestV0;
+#[cfg(feature = "blake3")]
+pub use adapters::{Blake3, Blake3Reader};
+#[cfg(feature = "std-hasher")]
+pub use adapters::{StdBuildHasher, StdHasher};
+pub use adapters::{VarOutput, Xof};
+#[cfg(feature = "xxh3")]
+pub use adapters::{Xxh3_64, Xxh3_128};
+pub use array_digest::{ArrayDigestV0, ArrayDigestV1};
+pub use dyn_digest::{
+    DynArrayDigestV0, DynArrayDigestV1, DynRecordDigestV0, DynRecordDigestV1, HashAlgorithm,
+};
+pub use error::DigestError;
+pub use record_digest::{MetadataMode, RecordDigestOptions, RecordDigestV0, RecordDigestV1};
+#[cfg(feature = "ipc")]
+pub use schema_digest::check_ipc_endianness;
+pub use schema_digest::{
+    DateUnitID, IntervalUnitID, TimeUnitID, TypeID, check_data_type, check_schema, encode_data_type,
+};
 pub use traits::{ArrayDigest, RecordDigest};
@@ -1,25 +1,94 @@
-use crate::{ArrayDigest, ArrayDigestV0, RecordDigest};
+use crate::array_digest::{ArrayDigestImpl, DigestVersion};
+use crate::{DigestError, RecordDigest};
 use arrow::{
-    array::{Array, ArrayRef, StructArray},
-    buffer::NullBuffer,
-    datatypes::{DataType, Field, Fields, Schema},
+    datatypes::{DataType, FieldRef, Fields, Schema},
     record_batch::RecordBatch,
 };
 use digest::{Digest, Output, OutputSizeUser};
 
 /////////////////////////////////////////////////////////////////////////////////////////
 
-pub struct RecordDigestV0<Dig: Digest> {
-    columns: Vec<ArrayDigestV0<Dig>>,
-    hasher: Dig,
+/// Options that control which properties of the schema contribute to the digest
+#[derive(Debug, Clone, Default)]
+pub struct RecordDigestOptions {
+    // Default depends on the version of the digest
+    pub(crate) include_nullability: Option<bool>,
+    pub(crate) metadata_mode: MetadataMode,
+    pub(crate) include_row_count: bool,
+}
+
+impl RecordDigestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mixes nullability of every field into the schema hash (off by default in
+    /// `RecordDigestV0` and on by default in `RecordDigestV1`).
+    pub fn with_nullability(mut self, include_nullability: bool) -> Self {
+        self.include_nullability = Some(include_nullability);
+        self
+    }
+
+    /// Selects which entries of schema and field metadata are mixed into the schema hash
+    /// (none by default).
+    pub fn with_metadata(mut self, metadata_mode: MetadataMode) -> Self {
+        self.metadata_mode = metadata_mode;
+        self
+    }
+
+    /// Mixes the total number of rows across all batches into the digest (off by default).
+    ///
+    /// Makes batches without any leaf columns (e.g. with zero columns or only empty structs)
+    /// hash differently depending on the number of rows.
+    pub fn with_row_count(mut self, include_row_count: bool) -> Self {
+        self.include_row_count = include_row_count;
+        self
+    }
+}
+
+/// Controls which metadata entries contribute to the digest
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum MetadataMode {
+    /// Metadata is ignored
+    #[default]
+    None,
+    /// All metadata entries are hashed
+    All,
+    /// Only entries with the specified keys are hashed
+    Keys(Vec<String>),
 }
 
 /////////////////////////////////////////////////////////////////////////////////////////
 
+pub struct RecordDigestV0<Dig: Digest>(RecordDigestImpl<Dig>);
+
 impl<Dig: Digest> OutputSizeUser for RecordDigestV0<Dig> {
     type OutputSize = Dig::OutputSize;
 }
 
+impl<Dig: Digest> RecordDigestV0<Dig> {
+    /// Same as [`RecordDigest::new`] but with options that control which properties of the
+    /// schema contribute to the digest
+    pub fn new_with_options(schema: &Schema, options: &RecordDigestOptions) -> Self {
+        Self(RecordDigestImpl::new(
+            DigestVersion::V0,
+            schema,
+            options,
+            Dig::new(),
+        ))
+    }
+
+    /// Same as [`RecordDigestV0::new_with_options`] but returns an error instead of
+    /// panicking
+    pub fn try_new_with_options(
+        schema: &Schema,
+        options: &RecordDigestOptions,
+    ) -> Result<Self, DigestError> {
+        crate::check_schema(schema)?;
+        Ok(Self::new_with_options(schema, options))
+    }
+}
+
 impl<Dig: Digest> RecordDigest for RecordDigestV0<Dig> {
     fn digest(batch: &RecordBatch) -> Output<Dig> {
         let mut d = Self::new(batch.schema().as_ref());
@@ -28,28 +97,255 @@ impl<Dig: Digest> RecordDigest for RecordDigestV0<Dig> {
     }
 
     fn new(schema: &Schema) -> Self {
-        let mut hasher = Dig::new();
+        Self::new_with_options(schema, &RecordDigestOptions::default())
+    }
+
+    fn update(&mut self, batch: &RecordBatch) {
+        self.0.update(batch);
+    }
+
+    fn try_update(&mut self, batch: &RecordBatch) -> Result<(), DigestError> {
+        self.0.try_update(batch)
+    }
+
+    fn finalize(self) -> Output<Dig> {
+        self.0.finalize()
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+// Uses ArrayDigestV1 encoding for all columns
+pub struct RecordDigestV1<Dig: Digest>(RecordDigestImpl<Dig>);
+
+impl<Dig: Digest> OutputSizeUser for RecordDigestV1<Dig> {
+    type OutputSize = Dig::OutputSize;
+}
+
+impl<Dig: Digest> RecordDigestV1<Dig> {
+    /// Same as [`RecordDigest::new`] but with options that control which properties of the
+    /// schema contribute to the digest
+    pub fn new_with_options(schema: &Schema, options: &RecordDigestOptions) -> Self {
+        Self(RecordDigestImpl::new(
+            DigestVersion::V1,
+            schema,
+            options,
+            Dig::new(),
+        ))
+    }
+
+    /// Same as [`RecordDigestV1::new_with_options`] but returns an error instead of
+    /// panicking
+    pub fn try_new_with_options(
+        schema: &Schema,
+        options: &RecordDigestOptions,
+    ) -> Result<Self, DigestError> {
+        crate::check_schema(schema)?;
+        Ok(Self::new_with_options(schema, options))
+    }
+}
+
+impl<Dig: Digest> RecordDigest for RecordDigestV1<Dig> {
+    fn digest(batch: &RecordBatch) -> Output<Dig> {
+        let mut d = Self::new(batch.schema().as_ref());
+        d.update(batch);
+        d.finalize()
+    }
+
+    fn new(schema: &Schema) -> Self {
+        Self::new_with_options(schema, &RecordDigestOptions::default())
+    }
+
+    fn update(&mut self, batch: &RecordBatch) {
+        self.0.update(batch);
+    }
+
+    fn try_update(&mut self, batch: &RecordBatch) -> Result<(), DigestError> {
+        self.0.try_update(batch)
+    }
+
+    fn finalize(self) -> Output<Dig> {
+        self.0.finalize()
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+pub(crate) struct RecordDigestImpl<H: crate::hasher::HashSink> {
+    version: DigestVersion,
+    // Fields of the schema along with encodings of their types, used to validate batches
+    fields: Fields,
+    field_type_encodings: Vec<Vec<u8>>,
+    columns: Vec<ArrayDigestImpl<H>>,
+    hasher: H,
+    // Total number of rows seen so far, when row count is included
+    num_rows: Option<u64>,
+}
+
+impl<H: crate::hasher::HashSink> RecordDigestImpl<H> {
+    pub fn new(
+        version: DigestVersion,
+        schema: &Schema,
+        options: &RecordDigestOptions,
+        mut hasher: H,
+    ) -> Self {
         let mut columns = Vec::new();
 
-        Self::walk_nested_fields(schema.fields(), 0, &mut |field, level| {
-            hasher.update((field.name().len() as u64).to_le_bytes());
-            hasher.update(field.name().as_bytes());
-            hasher.update((level as u64).to_le_bytes());
+        match version {
+            DigestVersion::V0 => {
+                crate::utils::walk_nested_fields(schema.fields(), 0, &mut |field, level| {
+                    hasher.update((field.name().len() as u64).to_le_bytes());
+                    hasher.update(field.name().as_bytes());
+                    hasher.update((level as u64).to_le_bytes());
+                    if options.include_nullability.unwrap_or(false) {
+                        hasher.update([field.is_nullable() as u8]);
+                    }
+                    crate::schema_digest::hash_metadata(
+                        field.metadata(),
+                        &options.metadata_mode,
+                        &mut hasher,
+                    );
 
-            match field.data_type() {
-                DataType::Struct(_) => (),
-                _ => columns.push(ArrayDigestV0::new(field.data_type())),
+                    match field.data_type() {
+                        DataType::Struct(_) => (),
+                        _ => columns.push(ArrayDigestImpl::new(
+                            version,
+                            field.data_type(),
+                            hasher.fork(),
+                        )),
+                    }
+                });
+
+                crate::schema_digest::hash_metadata(
+                    schema.metadata(),
+                    &options.metadata_mode,
+                    &mut hasher,
+                );
+            }
+            // V1+ has a digest per top-level column that handles nested fields itself
+            _ => {
+                crate::schema_digest::hash_schema(
+                    schema,
+                    options.include_nullability.unwrap_or(true),
+                    &options.metadata_mode,
+                    &mut hasher,
+                );
+
+                for field in schema.fields() {
+                    columns.push(ArrayDigestImpl::new(
+                        version,
+                        field.data_type(),
+                        hasher.fork(),
+                    ));
+                }
             }
-        });
+        }
+
+        Self {
+            version,
+            fields: schema.fields().clone(),
+            field_type_encodings: schema
+                .fields()
+                .iter()
+                .map(|f| crate::schema_digest::encode_valid_data_type(f.data_type()))
+                .collect(),
+            columns,
+            hasher,
+            num_rows: options.include_row_count.then_some(0),
+        }
+    }
+
+    // Batches are validated only in debug builds - use `try_update()` to handle mismatches
+    pub fn update(&mut self, batch: &RecordBatch) {
+        if cfg!(debug_assertions)
+            && let Err(err) = self.check_batch_schema(batch.schema().as_ref())
+        {
+            panic!("{err}");
+        }
+        self.update_unchecked(batch);
+    }
 
-        Self { columns, hasher }
+    pub fn try_update(&mut self, batch: &RecordBatch) -> Result<(), DigestError> {
+        // Schema of the digest itself is validated on creation, as encoding it would panic on
+        // unsupported types
+        self.check_batch_schema(batch.schema().as_ref())?;
+        self.update_unchecked(batch);
+        Ok(())
     }
 
-    fn update(&mut self, batch: &RecordBatch) {
+    // Batches may use different but logically equivalent encodings of types (e.g. `Utf8`
+    // and `LargeUtf8`) as long as they are hashed identically. Types are compared directly
+    // first, as most batches share the schema of the digest, and only differing types are
+    // encoded and validated.
+    fn check_batch_schema(&self, schema: &Schema) -> Result<(), DigestError> {
+        let actual_fields = schema.fields();
+
+        if std::ptr::eq::<[FieldRef]>(&self.fields[..], &actual_fields[..]) {
+            return Ok(());
+        }
+
+        for (i, expected) in self.fields.iter().enumerate() {
+            let mismatch = |actual: Option<&DataType>| DigestError::SchemaMismatch {
+                path: expected.name().clone(),
+                expected: Some(expected.data_type().clone()),
+                actual: actual.cloned(),
+            };
+
+            match actual_fields.get(i) {
+                Some(actual) if actual.name() == expected.name() => {
+                    if actual.data_type() != expected.data_type() {
+                        crate::schema_digest::check_field(actual)?;
+                        if crate::schema_digest::encode_valid_data_type(actual.data_type())
+                            != self.field_type_encodings[i]
+                        {
+                            return Err(mismatch(Some(actual.data_type())));
+                        }
+
+                        // V0 flattens struct columns of the batch
+                        if self.version == DigestVersion::V0
+                            && !crate::utils::flattens_identically(
+                                expected.data_type(),
+                                actual.data_type(),
+                                true,
+                            )
+                        {
+                            return Err(mismatch(Some(actual.data_type())));
+                        }
+                    }
+                }
+                _ => return Err(mismatch(None)),
+            }
+        }
+
+        if let Some(extra) = actual_fields.get(self.fields.len()) {
+            return Err(DigestError::SchemaMismatch {
+                path: extra.name().clone(),
+                expected: None,
+                actual: Some(extra.data_type().clone()),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn update_unchecked(&mut self, batch: &RecordBatch) {
+        if let Some(num_rows) = &mut self.num_rows {
+            *num_rows += batch.num_rows() as u64;
+        }
+
+        // V1+ hashes top-level columns as is, letting struct digests record their validity
+        if self.version != DigestVersion::V0 {
+            for (col_digest, array) in self.columns.iter_mut().zip(batch.columns()) {
+                col_digest.update(array.as_ref(), None);
+            }
+            return;
+        }
+
         let mut col_index = 0;
-        Self::walk_nested_columns(
+        crate::utils::walk_nested_columns(
             batch.columns().iter(),
             None,
+            true,
             &mut |array, parent_null_bitmap| {
                 let col_digest = &mut self.columns[col_index];
                 col_digest.update(array.as_ref(), parent_null_bitmap);
@@ -58,55 +354,15 @@ impl<Dig: Digest> RecordDigest for RecordDigestV0<Dig> {
         );
     }
 
-    fn finalize(mut self) -> Output<Dig> {
+    pub fn finalize(mut self) -> H::Output {
         for c in self.columns {
             let column_hash = c.finalize();
-            self.hasher.update(column_hash.as_slice());
+            self.hasher.update(column_hash);
         }
-        self.hasher.finalize()
-    }
-}
-
-impl<Dig: Digest> RecordDigestV0<Dig> {
-    fn walk_nested_fields(fields: &Fields, level: usize, fun: &mut impl FnMut(&Field, usize)) {
-        for field in fields {
-            match field.data_type() {
-                DataType::Struct(nested_fields) => {
-                    fun(field, level);
-                    Self::walk_nested_fields(nested_fields, level + 1, fun);
-                }
-                _ => fun(field, level),
-            }
-        }
-    }
-
-    fn walk_nested_columns<'a>(
-        arrays: impl Iterator<Item = &'a ArrayRef>,
-        parent_null_bitmap: Option<&NullBuffer>,
-        fun: &mut impl FnMut(&ArrayRef, Option<&NullBuffer>),
-    ) {
-        for array in arrays {
-            match array.data_type() {
-                DataType::Struct(_) => {
-                    let array = array.as_any().downcast_ref::<StructArray>().unwrap();
-                    let array_data = array.to_data();
-
-                    let combined_nulls = crate::utils::maybe_combine_null_buffers(
-                        parent_null_bitmap,
-                        array_data.nulls(),
-                    );
-
-                    for i in 0..array.num_columns() {
-                        Self::walk_nested_columns(
-                            [array.column(i)].into_iter(),
-                            combined_nulls.as_option(),
-                            fun,
-                        );
-                    }
-                }
-                _ => fun(array, parent_null_bitmap),
-            }
+        if let Some(num_rows) = self.num_rows {
+            self.hasher.update(num_rows.to_le_bytes());
         }
+        self.hasher.finalize()
     }
 }
 
@@ -117,13 +373,18 @@ impl<Dig: Digest> RecordDigestV0<Dig> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::DigestError;
     use arrow::{
-        array::{Array, Int32Array, StringArray},
-        buffer::Buffer,
-        datatypes::{DataType, Field, Schema},
-        record_batch::RecordBatch,
+        array::{
+            Array, ArrayRef, DictionaryArray, Int8Array, Int32Array, LargeStringArray, ListArray,
+            NullArray, StringArray, StringViewArray, StructArray,
+        },
+        buffer::{Buffer, NullBuffer, OffsetBuffer},
+        datatypes::{DataType, Field, Fields, Int8Type, Schema},
+        record_batch::{RecordBatch, RecordBatchOptions},
     };
     use sha3::Sha3_256;
+    use std::collections::HashMap;
     use std::sync::Arc;
 
     #[test]
@@ -255,6 +516,784 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_batch_nested_nulls() {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "b",
+            DataType::Struct(Fields::from(vec![Field::new("c", DataType::Int32, true)])),
+            true,
+        )]));
+
+        let make_batch = |c: Vec<Option<i32>>| {
+            let b = Arc::new(StructArray::from((
+                vec![(
+                    Arc::new(Field::new("c", DataType::Int32, true)),
+                    Arc::new(Int32Array::from(c)) as ArrayRef,
+                )],
+                Buffer::from([0b101]),
+            )));
+            RecordBatch::try_new(schema.clone(), vec![b]).unwrap()
+        };
+
+        // Values of a field under a null struct don't matter, whether they are null or not
+        let digest = RecordDigestV1::<Sha3_256>::digest(&make_batch(vec![Some(1), Some(2), None]));
+        assert_eq!(
+            digest,
+            RecordDigestV1::<Sha3_256>::digest(&make_batch(vec![Some(1), Some(7), None])),
+        );
+        assert_eq!(
+            digest,
+            RecordDigestV1::<Sha3_256>::digest(&make_batch(vec![Some(1), None, None])),
+        );
+    }
+
+    #[test]
+    fn test_batch_list_of_structs() {
+        let item_fields = Fields::from(vec![Field::new("x", DataType::Int32, true)]);
+        let items = StructArray::new(
+            item_fields.clone(),
+            vec![Arc::new(Int32Array::from(vec![1, 2, 3, 4]))],
+            None,
+        );
+        let list = ListArray::new(
+            Arc::new(Field::new_list_field(DataType::Struct(item_fields), true)),
+            OffsetBuffer::new(vec![0, 1, 1, 4].into()),
+            Arc::new(items),
+            Some(NullBuffer::from(vec![true, false, true])),
+        );
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", list.data_type().clone(), true),
+        ]));
+
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(vec![1, 2, 3])), Arc::new(list)],
+        )
+        .unwrap();
+
+        let mut digest = RecordDigestV0::<sha3::Sha3_256>::new(&schema);
+        digest.update(&batch.slice(0, 2));
+        digest.update(&batch.slice(2, 1));
+
+        assert_eq!(
+            digest.finalize(),
+            RecordDigestV0::<sha3::Sha3_256>::digest(&batch),
+        );
+    }
+
+    #[test]
+    fn test_batch_null_column() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Null, true),
+        ]));
+
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2, 3])),
+                Arc::new(NullArray::new(3)),
+            ],
+        )
+        .unwrap();
+
+        let schema_int = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, true),
+        ]));
+
+        let batch_int = RecordBatch::try_new(
+            schema_int,
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2, 3])),
+                Arc::new(Int32Array::from(vec![None, None, None])),
+            ],
+        )
+        .unwrap();
+
+        assert_ne!(
+            RecordDigestV0::<sha3::Sha3_256>::digest(&batch),
+            RecordDigestV0::<sha3::Sha3_256>::digest(&batch_int),
+        );
+
+        let mut digest = RecordDigestV0::<sha3::Sha3_256>::new(&schema);
+        digest.update(&batch.slice(0, 1));
+        digest.update(&batch.slice(1, 2));
+
+        assert_eq!(
+            digest.finalize(),
+            RecordDigestV0::<sha3::Sha3_256>::digest(&batch),
+        );
+    }
+
+    #[test]
+    fn test_batch_v1() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+
+        let batch_nulls = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![None, Some(1), None])),
+                Arc::new(StringArray::from(vec![None, Some("a"), None])),
+            ],
+        )
+        .unwrap();
+
+        let batch_zeros = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![Some(0), Some(1), Some(0)])),
+                Arc::new(StringArray::from(vec![Some(""), Some("a"), Some("")])),
+            ],
+        )
+        .unwrap();
+
+        assert_ne!(
+            RecordDigestV1::<Sha3_256>::digest(&batch_nulls),
+            RecordDigestV1::<Sha3_256>::digest(&batch_zeros),
+        );
+
+        let mut digest = RecordDigestV1::<Sha3_256>::new(&schema);
+        digest.update(&batch_nulls.slice(0, 1));
+        digest.update(&batch_nulls.slice(1, 2));
+
+        assert_eq!(
+            digest.finalize(),
+            RecordDigestV1::<Sha3_256>::digest(&batch_nulls),
+        );
+    }
+
+    #[test]
+    fn test_batch_v1_nested_nulls() {
+        let inner_fields = Fields::from(vec![
+            Field::new("c", DataType::Utf8, true),
+            Field::new("d", DataType::Int32, true),
+        ]);
+        let outer_fields = Fields::from(vec![Field::new(
+            "b",
+            DataType::Struct(inner_fields.clone()),
+            true,
+        )]);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("x", DataType::Struct(outer_fields.clone()), true),
+        ]));
+
+        let make_batch = |inner_nulls: Option<NullBuffer>, outer_nulls: Option<NullBuffer>| {
+            let b = StructArray::new(
+                inner_fields.clone(),
+                vec![
+                    Arc::new(StringArray::from(vec![Some("a"), None, None])),
+                    Arc::new(Int32Array::from(vec![Some(1), None, None])),
+                ],
+                inner_nulls,
+            );
+            let x = StructArray::new(outer_fields.clone(), vec![Arc::new(b)], outer_nulls);
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![Arc::new(Int32Array::from(vec![1, 2, 3])), Arc::new(x)],
+            )
+            .unwrap()
+        };
+
+        // Inner struct is valid with all fields being null
+        let batch_fields_null = make_batch(None, None);
+        // Inner struct is null
+        let batch_inner_null = make_batch(Some(NullBuffer::from(vec![true, false, false])), None);
+        // Outer struct is null
+        let batch_outer_null = make_batch(None, Some(NullBuffer::from(vec![true, false, false])));
+
+        // V0 can't tell these apart
+        assert_eq!(
+            RecordDigestV0::<Sha3_256>::digest(&batch_fields_null),
+            RecordDigestV0::<Sha3_256>::digest(&batch_inner_null),
+        );
+        assert_eq!(
+            RecordDigestV0::<Sha3_256>::digest(&batch_fields_null),
+            RecordDigestV0::<Sha3_256>::digest(&batch_outer_null),
+        );
+
+        assert_ne!(
+            RecordDigestV1::<Sha3_256>::digest(&batch_fields_null),
+            RecordDigestV1::<Sha3_256>::digest(&batch_inner_null),
+        );
+        assert_ne!(
+            RecordDigestV1::<Sha3_256>::digest(&batch_fields_null),
+            RecordDigestV1::<Sha3_256>::digest(&batch_outer_null),
+        );
+        assert_ne!(
+            RecordDigestV1::<Sha3_256>::digest(&batch_inner_null),
+            RecordDigestV1::<Sha3_256>::digest(&batch_outer_null),
+        );
+
+        // Values hidden by a null parent are ignored
+        let b = StructArray::new(
+            inner_fields.clone(),
+            vec![
+                Arc::new(StringArray::from(vec![Some("a"), Some("garbage"), None])),
+                Arc::new(Int32Array::from(vec![Some(1), Some(42), None])),
+            ],
+            None,
+        );
+        let x = StructArray::new(
+            outer_fields.clone(),
+            vec![Arc::new(b)],
+            Some(NullBuffer::from(vec![true, false, false])),
+        );
+        let batch_garbage = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(vec![1, 2, 3])), Arc::new(x)],
+        )
+        .unwrap();
+
+        assert_eq!(
+            RecordDigestV1::<Sha3_256>::digest(&batch_outer_null),
+            RecordDigestV1::<Sha3_256>::digest(&batch_garbage),
+        );
+
+        // Independent of batch boundaries
+        let mut digest = RecordDigestV1::<Sha3_256>::new(&schema);
+        digest.update(&batch_inner_null.slice(0, 2));
+        digest.update(&batch_inner_null.slice(2, 1));
+
+        assert_eq!(
+            digest.finalize(),
+            RecordDigestV1::<Sha3_256>::digest(&batch_inner_null),
+        );
+    }
+
+    #[test]
+    fn test_schema_v1() {
+        let header =
+            |fields: Vec<Field>| RecordDigestV1::<Sha3_256>::new(&Schema::new(fields)).finalize();
+        let int = |name: &str| Field::new(name, DataType::Int32, true);
+        let strct = |name: &str, fields: Vec<Field>| {
+            Field::new(name, DataType::Struct(Fields::from(fields)), true)
+        };
+
+        assert_eq!(
+            header(vec![strct("a", vec![int("b")]), int("c")]),
+            header(vec![strct("a", vec![int("b")]), int("c")]),
+        );
+
+        // Same field in a different position of the tree
+        assert_ne!(
+            header(vec![strct("a", vec![int("b")]), int("c")]),
+            header(vec![strct("a", vec![int("b"), int("c")])]),
+        );
+        assert_ne!(
+            header(vec![strct("a", vec![]), int("b")]),
+            header(vec![strct("a", vec![int("b")])]),
+        );
+        assert_ne!(
+            header(vec![strct("a", vec![strct("b", vec![int("c")])])]),
+            header(vec![strct("a", vec![strct("b", vec![]), int("c")])]),
+        );
+
+        // Types and nullability
+        assert_ne!(
+            header(vec![int("a")]),
+            header(vec![Field::new("a", DataType::Int64, true)]),
+        );
+        assert_ne!(
+            header(vec![int("a")]),
+            header(vec![Field::new("a", DataType::Int32, false)]),
+        );
+        assert_ne!(
+            header(vec![strct("a", vec![int("b")])]),
+            header(vec![strct(
+                "a",
+                vec![Field::new("b", DataType::Int32, false)]
+            )]),
+        );
+
+        // Fields nested in lists, maps and unions are nodes of the tree too
+        let list = |name: &str, item: Field| Field::new(name, DataType::List(Arc::new(item)), true);
+        let item = |fields: Vec<Field>| {
+            Field::new_list_field(DataType::Struct(Fields::from(fields)), true)
+        };
+
+        assert_ne!(
+            header(vec![list(
+                "l",
+                item(vec![strct("a", vec![int("b")]), int("c")])
+            )]),
+            header(vec![list(
+                "l",
+                item(vec![strct("a", vec![int("b"), int("c")])])
+            )]),
+        );
+        assert_ne!(
+            header(vec![list("l", item(vec![int("a")]))]),
+            header(vec![list(
+                "l",
+                item(vec![Field::new("a", DataType::Int32, false)])
+            )]),
+        );
+        assert_ne!(
+            header(vec![Field::new_map(
+                "m",
+                "entries",
+                Field::new("keys", DataType::Utf8, false),
+                Field::new("values", DataType::Int32, true),
+                false,
+                true,
+            )]),
+            header(vec![Field::new_map(
+                "m",
+                "entries",
+                Field::new("keys", DataType::Utf8, false),
+                Field::new("values", DataType::Int32, false),
+                false,
+                true,
+            )]),
+        );
+
+        // Names of list items are not part of the schema, same as in the type
+        assert_eq!(
+            header(vec![list("l", Field::new("item", DataType::Int32, true))]),
+            header(vec![list(
+                "l",
+                Field::new("element", DataType::Int32, true)
+            )]),
+        );
+    }
+
+    #[test]
+    fn test_batch_nullability_option() {
+        let make_batch = |nullable: bool| {
+            RecordBatch::try_new(
+                Arc::new(Schema::new(vec![Field::new(
+                    "a",
+                    DataType::Int32,
+                    nullable,
+                )])),
+                vec![Arc::new(Int32Array::from(vec![1, 2, 3]))],
+            )
+            .unwrap()
+        };
+        let digest = |batch: &RecordBatch, options: &RecordDigestOptions| {
+            let mut d = RecordDigestV0::<Sha3_256>::new_with_options(&batch.schema(), options);
+            d.update(batch);
+            d.finalize()
+        };
+
+        // Ignored by default
+        assert_eq!(
+            digest(&make_batch(false), &RecordDigestOptions::new()),
+            digest(&make_batch(true), &RecordDigestOptions::new()),
+        );
+        assert_eq!(
+            digest(&make_batch(false), &RecordDigestOptions::new()),
+            RecordDigestV0::<Sha3_256>::digest(&make_batch(false)),
+        );
+
+        let options = RecordDigestOptions::new().with_nullability(true);
+        assert_ne!(
+            digest(&make_batch(false), &options),
+            digest(&make_batch(true), &options),
+        );
+        assert_ne!(
+            digest(&make_batch(false), &options),
+            digest(&make_batch(false), &RecordDigestOptions::new()),
+        );
+
+        // V1 hashes nullability by default, unless disabled explicitly
+        let digest_v1 = |batch: &RecordBatch, options: &RecordDigestOptions| {
+            let mut d = RecordDigestV1::<Sha3_256>::new_with_options(&batch.schema(), options);
+            d.update(batch);
+            d.finalize()
+        };
+
+        assert_ne!(
+            digest_v1(&make_batch(false), &RecordDigestOptions::new()),
+            digest_v1(&make_batch(true), &RecordDigestOptions::new()),
+        );
+        assert_eq!(
+            digest_v1(&make_batch(false), &options),
+            RecordDigestV1::<Sha3_256>::digest(&make_batch(false)),
+        );
+
+        let options = RecordDigestOptions::new().with_nullability(false);
+        assert_eq!(
+            digest_v1(&make_batch(false), &options),
+            digest_v1(&make_batch(true), &options),
+        );
+        assert_ne!(
+            digest_v1(&make_batch(false), &options),
+            RecordDigestV1::<Sha3_256>::digest(&make_batch(false)),
+        );
+    }
+
+    #[test]
+    fn test_schema_metadata_option() {
+        let header = |schema_md: &[(&str, &str)], field_md: &[(&str, &str)], options| {
+            let to_map = |md: &[(&str, &str)]| {
+                md.iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect::<HashMap<_, _>>()
+            };
+            let schema = Schema::new(vec![
+                Field::new("a", DataType::Int32, true).with_metadata(to_map(field_md)),
+            ])
+            .with_metadata(to_map(schema_md));
+            (
+                RecordDigestV0::<Sha3_256>::new_with_options(&schema, &options).finalize(),
+                RecordDigestV1::<Sha3_256>::new_with_options(&schema, &options).finalize(),
+            )
+        };
+
+        let all = || RecordDigestOptions::new().with_metadata(MetadataMode::All);
+        let unit = || {
+            RecordDigestOptions::new().with_metadata(MetadataMode::Keys(vec!["unit".to_string()]))
+        };
+
+        // Ignored by default
+        assert_eq!(
+            header(&[], &[], RecordDigestOptions::new()),
+            header(&[("x", "1")], &[("unit", "m")], RecordDigestOptions::new()),
+        );
+
+        // Order of entries doesn't matter
+        assert_eq!(
+            header(&[("x", "1"), ("y", "2")], &[], all()),
+            header(&[("y", "2"), ("x", "1")], &[], all()),
+        );
+
+        assert_ne!(
+            header(&[], &[], all()).0,
+            header(&[("x", "1")], &[], all()).0
+        );
+        assert_ne!(
+            header(&[], &[], all()).1,
+            header(&[("x", "1")], &[], all()).1
+        );
+        assert_ne!(
+            header(&[], &[("unit", "m")], all()).0,
+            header(&[], &[("unit", "s")], all()).0,
+        );
+        assert_ne!(
+            header(&[], &[("unit", "m")], all()).1,
+            header(&[], &[("unit", "s")], all()).1,
+        );
+
+        // Schema vs field metadata
+        assert_ne!(
+            header(&[("unit", "m")], &[], all()).0,
+            header(&[], &[("unit", "m")], all()).0,
+        );
+        assert_ne!(
+            header(&[("unit", "m")], &[], all()).1,
+            header(&[], &[("unit", "m")], all()).1,
+        );
+
+        // Only selected keys
+        assert_eq!(
+            header(&[("x", "1")], &[("unit", "m")], unit()),
+            header(&[], &[("unit", "m"), ("y", "2")], unit()),
+        );
+        assert_ne!(
+            header(&[], &[("unit", "m")], unit()).0,
+            header(&[], &[("unit", "s")], unit()).0,
+        );
+        assert_ne!(
+            header(&[], &[("unit", "m")], unit()).1,
+            header(&[], &[("unit", "s")], unit()).1,
+        );
+    }
+
+    #[test]
+    fn test_batch_row_count_option() {
+        let empty_schema = Arc::new(Schema::empty());
+        let make_empty = |num_rows: usize| {
+            RecordBatch::try_new_with_options(
+                empty_schema.clone(),
+                vec![],
+                &RecordBatchOptions::new().with_row_count(Some(num_rows)),
+            )
+            .unwrap()
+        };
+        let digest = |batches: &[RecordBatch], options: &RecordDigestOptions| {
+            let mut d = RecordDigestV0::<Sha3_256>::new_with_options(&batches[0].schema(), options);
+            for batch in batches {
+                d.update(batch);
+            }
+            d.finalize()
+        };
+
+        // Ignored by default
+        assert_eq!(
+            digest(&[make_empty(0)], &RecordDigestOptions::new()),
+            digest(&[make_empty(1_000_000)], &RecordDigestOptions::new()),
+        );
+
+        let options = RecordDigestOptions::new().with_row_count(true);
+        assert_ne!(
+            digest(&[make_empty(0)], &options),
+            digest(&[make_empty(1_000_000)], &options),
+        );
+
+        // Total across all batches
+        assert_eq!(
+            digest(&[make_empty(3), make_empty(7)], &options),
+            digest(&[make_empty(10)], &options),
+        );
+
+        // Schema of empty structs
+        let fields = Fields::from(vec![Field::new(
+            "s",
+            DataType::Struct(Fields::empty()),
+            true,
+        )]);
+        let schema = Arc::new(Schema::new(fields));
+        let make_structs = |num_rows: usize| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![Arc::new(StructArray::new_empty_fields(num_rows, None))],
+            )
+            .unwrap()
+        };
+
+        assert_eq!(
+            digest(&[make_structs(1)], &RecordDigestOptions::new()),
+            digest(&[make_structs(5)], &RecordDigestOptions::new()),
+        );
+        assert_ne!(
+            digest(&[make_structs(1)], &options),
+            digest(&[make_structs(5)], &options),
+        );
+
+        let mut d = RecordDigestV1::<Sha3_256>::new_with_options(&empty_schema, &options);
+        d.update(&make_empty(0));
+        let mut d2 = RecordDigestV1::<Sha3_256>::new_with_options(&empty_schema, &options);
+        d2.update(&make_empty(5));
+        assert_ne!(d.finalize(), d2.finalize());
+    }
+
+    #[test]
+    fn test_batch_try_digest() {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(vec![1, 2, 3]))],
+        )
+        .unwrap();
+
+        assert_eq!(
+            RecordDigestV0::<Sha3_256>::try_digest(&batch).unwrap(),
+            RecordDigestV0::<Sha3_256>::digest(&batch),
+        );
+        assert_eq!(
+            RecordDigestV1::<Sha3_256>::try_digest(&batch).unwrap(),
+            RecordDigestV1::<Sha3_256>::digest(&batch),
+        );
+
+        let bad_map = DataType::Map(
+            Arc::new(Field::new("entries", DataType::Utf8, false)),
+            false,
+        );
+        let bad_schema = Schema::new(vec![Field::new(
+            "a",
+            DataType::Struct(Fields::from(vec![Field::new("m", bad_map.clone(), true)])),
+            true,
+        )]);
+
+        // Rejected before creating digests of the columns, which would panic
+        for err in [
+            RecordDigestV0::<Sha3_256>::try_new(&bad_schema).err(),
+            RecordDigestV1::<Sha3_256>::try_new(&bad_schema).err(),
+        ] {
+            assert_eq!(
+                err,
+                Some(DigestError::UnsupportedType {
+                    data_type: bad_map.clone(),
+                    path: "a.m".to_string(),
+                }),
+            );
+        }
+    }
+
+    #[test]
+    fn test_batch_schema_validation() {
+        let make_batch = |fields: Vec<(&str, ArrayRef)>| {
+            RecordBatch::try_from_iter(fields.into_iter().map(|(n, a)| (n.to_string(), a))).unwrap()
+        };
+        let a = || Arc::new(Int32Array::from(vec![1, 2])) as ArrayRef;
+
+        let batch = make_batch(vec![
+            ("a", a()),
+            ("b", Arc::new(StringArray::from(vec!["x", "y"]))),
+        ]);
+
+        // Equivalent encodings
+        let mut digest = RecordDigestV1::<Sha3_256>::new(&batch.schema());
+        digest
+            .try_update(&make_batch(vec![
+                ("a", a()),
+                ("b", Arc::new(LargeStringArray::from(vec!["x", "y"]))),
+            ]))
+            .unwrap();
+        digest
+            .try_update(&make_batch(vec![
+                ("a", a()),
+                ("b", Arc::new(StringViewArray::from(vec!["y", "x"]))),
+            ]))
+            .unwrap();
+
+        let mut expected = RecordDigestV1::<Sha3_256>::new(&batch.schema());
+        expected.update(&make_batch(vec![
+            ("a", a()),
+            ("b", Arc::new(StringArray::from(vec!["x", "y"]))),
+        ]));
+        expected.update(&make_batch(vec![
+            ("a", a()),
+            ("b", Arc::new(StringArray::from(vec!["y", "x"]))),
+        ]));
+        assert_eq!(digest.finalize(), expected.finalize());
+
+        // Mismatches
+        let mut digest = RecordDigestV0::<Sha3_256>::new(&batch.schema());
+
+        assert_eq!(
+            digest.try_update(&make_batch(vec![
+                ("a", a()),
+                ("b", Arc::new(StringArray::from(vec!["x", "y"]))),
+                ("c", a()),
+            ])),
+            Err(DigestError::SchemaMismatch {
+                path: "c".to_string(),
+                expected: None,
+                actual: Some(DataType::Int32),
+            }),
+        );
+
+        assert_eq!(
+            digest.try_update(&make_batch(vec![("a", a())])),
+            Err(DigestError::SchemaMismatch {
+                path: "b".to_string(),
+                expected: Some(DataType::Utf8),
+                actual: None,
+            }),
+        );
+
+        let err = digest
+            .try_update(&make_batch(vec![("a", a()), ("b", a())]))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            DigestError::SchemaMismatch {
+                path: "b".to_string(),
+                expected: Some(DataType::Utf8),
+                actual: Some(DataType::Int32),
+            },
+        );
+        assert_eq!(
+            err.to_string(),
+            "Field 'b' has type Int32 that doesn't match type Utf8 of the schema",
+        );
+
+        // Failed updates don't affect the digest
+        digest.update(&batch);
+        assert_eq!(
+            digest.finalize(),
+            RecordDigestV0::<Sha3_256>::digest(&batch)
+        );
+    }
+
+    #[test]
+    fn test_batch_schema_validation_struct_encodings() {
+        let struct_fields = Fields::from(vec![
+            Field::new("x", DataType::Int32, true),
+            Field::new("y", DataType::Utf8, true),
+        ]);
+        let plain = StructArray::new(
+            struct_fields.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2])),
+                Arc::new(StringArray::from(vec!["a", "b"])),
+            ],
+            None,
+        );
+        let dict = DictionaryArray::<Int8Type>::try_new(
+            Int8Array::from(vec![0, 1]),
+            Arc::new(plain.clone()),
+        )
+        .unwrap();
+
+        let make_batch = |b: ArrayRef| {
+            RecordBatch::try_from_iter([
+                ("a", Arc::new(Int32Array::from(vec![1, 2])) as ArrayRef),
+                ("b", b),
+            ])
+            .unwrap()
+        };
+        let plain_batch = make_batch(Arc::new(plain.clone()));
+        let dict_batch = make_batch(Arc::new(dict.clone()));
+
+        // V1 hashes struct columns as is, so encoded structs are interchangeable with plain ones
+        let mut digest = RecordDigestV1::<Sha3_256>::new(&plain_batch.schema());
+        digest.try_update(&dict_batch).unwrap();
+        assert_eq!(
+            digest.finalize(),
+            RecordDigestV1::<Sha3_256>::digest(&plain_batch)
+        );
+
+        // V0 flattens only plain struct columns
+        let mut digest = RecordDigestV0::<Sha3_256>::new(&plain_batch.schema());
+        assert_eq!(
+            digest.try_update(&dict_batch),
+            Err(DigestError::SchemaMismatch {
+                path: "b".to_string(),
+                expected: Some(plain.data_type().clone()),
+                actual: Some(dict.data_type().clone()),
+            }),
+        );
+
+        let mut digest = RecordDigestV0::<Sha3_256>::new(&dict_batch.schema());
+        assert_eq!(
+            digest.try_update(&plain_batch),
+            Err(DigestError::SchemaMismatch {
+                path: "b".to_string(),
+                expected: Some(dict.data_type().clone()),
+                actual: Some(plain.data_type().clone()),
+            }),
+        );
+
+        // Also when nested into a struct
+        let make_outer = |b: ArrayRef| {
+            make_batch(Arc::new(StructArray::from(vec![(
+                Arc::new(Field::new("c", b.data_type().clone(), true)),
+                b,
+            )])))
+        };
+
+        let mut digest =
+            RecordDigestV0::<Sha3_256>::new(&make_outer(Arc::new(plain.clone())).schema());
+        assert!(digest.try_update(&make_outer(Arc::new(dict))).is_err());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Field 'c' is not part of the schema")]
+    fn test_batch_schema_validation_panics() {
+        let batch =
+            RecordBatch::try_from_iter([("a", Arc::new(Int32Array::from(vec![1])) as ArrayRef)])
+                .unwrap();
+        let mut digest = RecordDigestV0::<Sha3_256>::new(&batch.schema());
+
+        let batch = RecordBatch::try_from_iter([
+            ("a", Arc::new(Int32Array::from(vec![1])) as ArrayRef),
+            ("c", Arc::new(Int32Array::from(vec![1])) as ArrayRef),
+        ])
+        .unwrap();
+        digest.update(&batch);
+    }
+
     /*#[test]
     fn test_batch_parquet() {
         use crate::{RecordDigest, RecordDigestV0};
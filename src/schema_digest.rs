@@ -1,49 +1,79 @@
-use arrow::datatypes::{DataType, TimeUnit};
-use digest::Digest;
+use crate::{DigestError, MetadataMode};
+use arrow::datatypes::{DataType, Field, FieldRef, IntervalUnit, Schema, TimeUnit};
+use std::collections::{BTreeMap, HashMap};
 
 /////////////////////////////////////////////////////////////////////////////////////////
 
-#[allow(dead_code)]
+/// Identifiers of logical types used in the type encoding (hashed as `u16`).
+///
+/// Discriminants of this enum and of [`DateUnitID`], [`TimeUnitID`] and [`IntervalUnitID`]
+/// are part of the stable wire format of digests - existing values never change, and new
+/// variants may only be added with new values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u16)]
-pub(crate) enum TypeID {
+#[non_exhaustive]
+pub enum TypeID {
+    /// `Null`
     Null = 0,
+    /// Signed and unsigned integers of all widths
     Int = 1,
+    /// `Float16`, `Float32` and `Float64`
     FloatingPoint = 2,
+    /// `Binary`, `LargeBinary`, `BinaryView` and `FixedSizeBinary`
     Binary = 3,
-    // LargeBinary = 3,
-    // FixedSizeBinary = 3,
+    /// `Utf8`, `LargeUtf8` and `Utf8View`
     Utf8 = 4,
-    // LargeUtf8 = 4,
+    /// `Boolean`
     Bool = 5,
+    /// Decimals of all widths
     Decimal = 6,
+    /// `Date32` and `Date64`
     Date = 7,
+    /// `Time32` and `Time64`
     Time = 8,
+    /// `Timestamp` of all units and time zones
     Timestamp = 9,
+    /// `Interval` of all units
     Interval = 10,
+    /// `List`, `LargeList`, `FixedSizeList`, `ListView` and `LargeListView`
     List = 11,
-    // LargeList = 11,
-    // FixedSizeList = 11,
+    /// `Struct`
     Struct = 12,
+    /// Sparse and dense `Union`
     Union = 13,
+    /// `Map`
     Map = 16,
+    /// `Duration` of all units
     Duration = 17,
 }
 
 /////////////////////////////////////////////////////////////////////////////////////////
 
+/// Identifiers of date units used in the type encoding (hashed as `u16`), see [`TypeID`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u16)]
-pub(crate) enum DateUnitID {
+#[non_exhaustive]
+pub enum DateUnitID {
+    /// Days since the epoch (`Date32`)
     Day = 0,
+    /// Milliseconds since the epoch (`Date64`)
     Millisecond = 1,
 }
 
 /////////////////////////////////////////////////////////////////////////////////////////
 
+/// Identifiers of time units used in the type encoding (hashed as `u16`), see [`TypeID`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u16)]
-pub(crate) enum TimeUnitID {
+#[non_exhaustive]
+pub enum TimeUnitID {
+    /// [`TimeUnit::Second`]
     Second = 0,
+    /// [`TimeUnit::Millisecond`]
     Millisecond = 1,
+    /// [`TimeUnit::Microsecond`]
     Microsecond = 2,
+    /// [`TimeUnit::Nanosecond`]
     Nanosecond = 3,
 }
 
@@ -60,142 +90,619 @@ impl From<&TimeUnit> for TimeUnitID {
 
 /////////////////////////////////////////////////////////////////////////////////////////
 
-// TODO: Support nesting
-pub(crate) fn hash_data_type<Dig: Digest>(data_type: &DataType, hasher: &mut Dig) {
+/// Identifiers of interval units used in the type encoding (hashed as `u16`), see [`TypeID`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u16)]
+#[non_exhaustive]
+pub enum IntervalUnitID {
+    /// [`IntervalUnit::YearMonth`]
+    YearMonth = 0,
+    /// [`IntervalUnit::DayTime`]
+    DayTime = 1,
+    /// [`IntervalUnit::MonthDayNano`]
+    MonthDayNano = 2,
+}
+
+impl From<&IntervalUnit> for IntervalUnitID {
+    fn from(u: &IntervalUnit) -> Self {
+        match u {
+            IntervalUnit::YearMonth => IntervalUnitID::YearMonth,
+            IntervalUnit::DayTime => IntervalUnitID::DayTime,
+            IntervalUnit::MonthDayNano => IntervalUnitID::MonthDayNano,
+        }
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+/// Returns the canonical encoding of the data type that is hashed as the header of every
+/// array digest and as part of the schema digest.
+///
+/// Logically equivalent types (e.g. `Utf8`, `LargeUtf8` and `Utf8View`, or dictionary- and
+/// run-end-encoded types and their value types) have identical encodings.
+///
+/// Returns [`DigestError::UnsupportedType`] for types rejected by [`check_data_type`].
+pub fn encode_data_type(data_type: &DataType) -> Result<Vec<u8>, DigestError> {
+    check_data_type(data_type)?;
+    Ok(encode_valid_data_type(data_type))
+}
+
+// Same as `encode_data_type()` for types that were already validated, panics otherwise
+pub(crate) fn encode_valid_data_type(data_type: &DataType) -> Vec<u8> {
+    let mut buf = Vec::new();
+    write_data_type(data_type, &mut buf);
+    buf
+}
+
+pub(crate) fn hash_data_type<H: crate::hasher::HashSink>(data_type: &DataType, hasher: &mut H) {
+    hasher.update(encode_valid_data_type(data_type));
+}
+
+fn write_data_type(data_type: &DataType, buf: &mut Vec<u8>) {
     match data_type {
         DataType::Null => {
-            hasher.update((TypeID::Null as u16).to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Null as u16).to_le_bytes());
         }
         DataType::Boolean => {
-            hasher.update((TypeID::Bool as u16).to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Bool as u16).to_le_bytes());
         }
         DataType::Int8 => {
-            hasher.update((TypeID::Int as u16).to_le_bytes());
-            hasher.update(1u8.to_le_bytes());
-            hasher.update(8u64.to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Int as u16).to_le_bytes());
+            buf.extend_from_slice(&1u8.to_le_bytes());
+            buf.extend_from_slice(&8u64.to_le_bytes());
         }
         DataType::Int16 => {
-            hasher.update((TypeID::Int as u16).to_le_bytes());
-            hasher.update(1u8.to_le_bytes());
-            hasher.update(16u64.to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Int as u16).to_le_bytes());
+            buf.extend_from_slice(&1u8.to_le_bytes());
+            buf.extend_from_slice(&16u64.to_le_bytes());
         }
         DataType::Int32 => {
-            hasher.update((TypeID::Int as u16).to_le_bytes());
-            hasher.update(1u8.to_le_bytes());
-            hasher.update(32u64.to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Int as u16).to_le_bytes());
+            buf.extend_from_slice(&1u8.to_le_bytes());
+            buf.extend_from_slice(&32u64.to_le_bytes());
         }
         DataType::Int64 => {
-            hasher.update((TypeID::Int as u16).to_le_bytes());
-            hasher.update(1u8.to_le_bytes());
-            hasher.update(64u64.to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Int as u16).to_le_bytes());
+            buf.extend_from_slice(&1u8.to_le_bytes());
+            buf.extend_from_slice(&64u64.to_le_bytes());
         }
         DataType::UInt8 => {
-            hasher.update((TypeID::Int as u16).to_le_bytes());
-            hasher.update(0u8.to_le_bytes());
-            hasher.update(8u64.to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Int as u16).to_le_bytes());
+            buf.extend_from_slice(&0u8.to_le_bytes());
+            buf.extend_from_slice(&8u64.to_le_bytes());
         }
         DataType::UInt16 => {
-            hasher.update((TypeID::Int as u16).to_le_bytes());
-            hasher.update(0u8.to_le_bytes());
-            hasher.update(16u64.to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Int as u16).to_le_bytes());
+            buf.extend_from_slice(&0u8.to_le_bytes());
+            buf.extend_from_slice(&16u64.to_le_bytes());
         }
         DataType::UInt32 => {
-            hasher.update((TypeID::Int as u16).to_le_bytes());
-            hasher.update(0u8.to_le_bytes());
-            hasher.update(32u64.to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Int as u16).to_le_bytes());
+            buf.extend_from_slice(&0u8.to_le_bytes());
+            buf.extend_from_slice(&32u64.to_le_bytes());
         }
         DataType::UInt64 => {
-            hasher.update((TypeID::Int as u16).to_le_bytes());
-            hasher.update(0u8.to_le_bytes());
-            hasher.update(64u64.to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Int as u16).to_le_bytes());
+            buf.extend_from_slice(&0u8.to_le_bytes());
+            buf.extend_from_slice(&64u64.to_le_bytes());
         }
         DataType::Float16 => {
-            hasher.update((TypeID::FloatingPoint as u16).to_le_bytes());
-            hasher.update(16u64.to_le_bytes());
+            buf.extend_from_slice(&(TypeID::FloatingPoint as u16).to_le_bytes());
+            buf.extend_from_slice(&16u64.to_le_bytes());
         }
         DataType::Float32 => {
-            hasher.update((TypeID::FloatingPoint as u16).to_le_bytes());
-            hasher.update(32u64.to_le_bytes());
+            buf.extend_from_slice(&(TypeID::FloatingPoint as u16).to_le_bytes());
+            buf.extend_from_slice(&32u64.to_le_bytes());
         }
         DataType::Float64 => {
-            hasher.update((TypeID::FloatingPoint as u16).to_le_bytes());
-            hasher.update(64u64.to_le_bytes());
+            buf.extend_from_slice(&(TypeID::FloatingPoint as u16).to_le_bytes());
+            buf.extend_from_slice(&64u64.to_le_bytes());
         }
         DataType::Timestamp(time_unit, time_zone) => {
-            hasher.update((TypeID::Timestamp as u16).to_le_bytes());
-            hasher.update((TimeUnitID::from(time_unit) as u16).to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Timestamp as u16).to_le_bytes());
+            buf.extend_from_slice(&(TimeUnitID::from(time_unit) as u16).to_le_bytes());
             match time_zone {
-                None => hasher.update([0u8]),
+                None => buf.extend_from_slice(&[0u8]),
                 Some(tz) => {
-                    hasher.update((tz.len() as u64).to_le_bytes());
-                    hasher.update(tz.as_bytes());
+                    buf.extend_from_slice(&(tz.len() as u64).to_le_bytes());
+                    buf.extend_from_slice(tz.as_bytes());
                 }
             }
         }
         DataType::Date32 => {
-            hasher.update((TypeID::Date as u16).to_le_bytes());
-            hasher.update(32u64.to_le_bytes());
-            hasher.update((DateUnitID::Day as u16).to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Date as u16).to_le_bytes());
+            buf.extend_from_slice(&32u64.to_le_bytes());
+            buf.extend_from_slice(&(DateUnitID::Day as u16).to_le_bytes());
         }
         DataType::Date64 => {
-            hasher.update((TypeID::Date as u16).to_le_bytes());
-            hasher.update(64u64.to_le_bytes());
-            hasher.update((DateUnitID::Millisecond as u16).to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Date as u16).to_le_bytes());
+            buf.extend_from_slice(&64u64.to_le_bytes());
+            buf.extend_from_slice(&(DateUnitID::Millisecond as u16).to_le_bytes());
         }
         DataType::Time32(time_unit) => {
-            hasher.update((TypeID::Time as u16).to_le_bytes());
-            hasher.update(32u64.to_le_bytes());
-            hasher.update((TimeUnitID::from(time_unit) as u16).to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Time as u16).to_le_bytes());
+            buf.extend_from_slice(&32u64.to_le_bytes());
+            buf.extend_from_slice(&(TimeUnitID::from(time_unit) as u16).to_le_bytes());
         }
         DataType::Time64(time_unit) => {
-            hasher.update((TypeID::Time as u16).to_le_bytes());
-            hasher.update(64u64.to_le_bytes());
-            hasher.update((TimeUnitID::from(time_unit) as u16).to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Time as u16).to_le_bytes());
+            buf.extend_from_slice(&64u64.to_le_bytes());
+            buf.extend_from_slice(&(TimeUnitID::from(time_unit) as u16).to_le_bytes());
+        }
+        DataType::Duration(time_unit) => {
+            buf.extend_from_slice(&(TypeID::Duration as u16).to_le_bytes());
+            buf.extend_from_slice(&(TimeUnitID::from(time_unit) as u16).to_le_bytes());
+        }
+        DataType::Interval(interval_unit) => {
+            buf.extend_from_slice(&(TypeID::Interval as u16).to_le_bytes());
+            buf.extend_from_slice(&(IntervalUnitID::from(interval_unit) as u16).to_le_bytes());
         }
-        DataType::Duration(_) => unimplemented!(),
-        DataType::Interval(_) => unimplemented!(),
         DataType::Binary
         | DataType::FixedSizeBinary(_)
         | DataType::LargeBinary
         | DataType::BinaryView => {
-            hasher.update((TypeID::Binary as u16).to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Binary as u16).to_le_bytes());
         }
         DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => {
-            hasher.update((TypeID::Utf8 as u16).to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Utf8 as u16).to_le_bytes());
         }
-        DataType::List(field) | DataType::FixedSizeList(field, _) | DataType::LargeList(field) => {
-            hasher.update((TypeID::List as u16).to_le_bytes());
-            hash_data_type(field.data_type(), hasher);
+        DataType::List(field)
+        | DataType::FixedSizeList(field, _)
+        | DataType::LargeList(field)
+        | DataType::ListView(field)
+        | DataType::LargeListView(field) => {
+            buf.extend_from_slice(&(TypeID::List as u16).to_le_bytes());
+            write_data_type(field.data_type(), buf);
         }
-        DataType::ListView(_) | DataType::LargeListView(_) => unimplemented!(),
-        DataType::Struct(_) => unimplemented!(),
-        DataType::Union(_, _) => unimplemented!(),
-        DataType::Dictionary(..) => unimplemented!(),
+        DataType::Struct(fields) => {
+            buf.extend_from_slice(&(TypeID::Struct as u16).to_le_bytes());
+            buf.extend_from_slice(&(fields.len() as u64).to_le_bytes());
+            for field in fields {
+                buf.extend_from_slice(&(field.name().len() as u64).to_le_bytes());
+                buf.extend_from_slice(field.name().as_bytes());
+                write_data_type(field.data_type(), buf);
+            }
+        }
+        // Union mode and type IDs are encoding concerns and are not hashed - fields are
+        // identified by their positions
+        DataType::Union(fields, _) => {
+            buf.extend_from_slice(&(TypeID::Union as u16).to_le_bytes());
+            buf.extend_from_slice(&(fields.len() as u64).to_le_bytes());
+            for (_, field) in fields.iter() {
+                buf.extend_from_slice(&(field.name().len() as u64).to_le_bytes());
+                buf.extend_from_slice(field.name().as_bytes());
+                write_data_type(field.data_type(), buf);
+            }
+        }
+        // Dictionary encoding is transparent - only the value type matters
+        DataType::Dictionary(_, value_type) => write_data_type(value_type, buf),
         DataType::Decimal32(p, s) => {
-            hasher.update((TypeID::Decimal as u16).to_le_bytes());
-            hasher.update(32u64.to_le_bytes());
-            hasher.update((*p as u64).to_le_bytes());
-            hasher.update((*s as u64).to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Decimal as u16).to_le_bytes());
+            buf.extend_from_slice(&32u64.to_le_bytes());
+            buf.extend_from_slice(&(*p as u64).to_le_bytes());
+            buf.extend_from_slice(&(*s as u64).to_le_bytes());
         }
         DataType::Decimal64(p, s) => {
-            hasher.update((TypeID::Decimal as u16).to_le_bytes());
-            hasher.update(64u64.to_le_bytes());
-            hasher.update((*p as u64).to_le_bytes());
-            hasher.update((*s as u64).to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Decimal as u16).to_le_bytes());
+            buf.extend_from_slice(&64u64.to_le_bytes());
+            buf.extend_from_slice(&(*p as u64).to_le_bytes());
+            buf.extend_from_slice(&(*s as u64).to_le_bytes());
         }
         DataType::Decimal128(p, s) => {
-            hasher.update((TypeID::Decimal as u16).to_le_bytes());
-            hasher.update(128u64.to_le_bytes());
-            hasher.update((*p as u64).to_le_bytes());
-            hasher.update((*s as u64).to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Decimal as u16).to_le_bytes());
+            buf.extend_from_slice(&128u64.to_le_bytes());
+            buf.extend_from_slice(&(*p as u64).to_le_bytes());
+            buf.extend_from_slice(&(*s as u64).to_le_bytes());
         }
         DataType::Decimal256(p, s) => {
-            hasher.update((TypeID::Decimal as u16).to_le_bytes());
-            hasher.update(256u64.to_le_bytes());
-            hasher.update((*p as u64).to_le_bytes());
-            hasher.update((*s as u64).to_le_bytes());
+            buf.extend_from_slice(&(TypeID::Decimal as u16).to_le_bytes());
+            buf.extend_from_slice(&256u64.to_le_bytes());
+            buf.extend_from_slice(&(*p as u64).to_le_bytes());
+            buf.extend_from_slice(&(*s as u64).to_le_bytes());
+        }
+        DataType::Map(entries_field, keys_sorted) => {
+            let DataType::Struct(entries_fields) = entries_field.data_type() else {
+                panic!(
+                    "Map entries must be a struct but got {}",
+                    entries_field.data_type()
+                );
+            };
+            buf.extend_from_slice(&(TypeID::Map as u16).to_le_bytes());
+            // Names of entries, key, and value fields vary between implementations and are
+            // not considered part of the type
+            for field in entries_fields {
+                write_data_type(field.data_type(), buf);
+            }
+            buf.extend_from_slice(&[*keys_sorted as u8]);
+        }
+        // Run-end encoding is transparent - only the value type matters
+        DataType::RunEndEncoded(_, values_field) => write_data_type(values_field.data_type(), buf),
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+// Hashes every node of the schema tree in the depth-first order, where the full path of
+// a node makes its position in the tree explicit, so moving a field to a different parent
+// always changes the result
+pub(crate) fn hash_schema<H: crate::hasher::HashSink>(
+    schema: &Schema,
+    include_nullability: bool,
+    metadata_mode: &MetadataMode,
+    hasher: &mut H,
+) {
+    hasher.update((schema.fields().len() as u64).to_le_bytes());
+    hash_schema_fields(
+        schema
+            .fields()
+            .iter()
+            .map(|f| (f.name().as_str(), f.as_ref())),
+        include_nullability,
+        metadata_mode,
+        &mut Vec::new(),
+        hasher,
+    );
+    hash_metadata(schema.metadata(), metadata_mode, hasher);
+}
+
+fn hash_schema_fields<'a, H: crate::hasher::HashSink>(
+    fields: impl IntoIterator<Item = (&'a str, &'a Field)>,
+    include_nullability: bool,
+    metadata_mode: &MetadataMode,
+    path: &mut Vec<&'a str>,
+    hasher: &mut H,
+) {
+    for (name, field) in fields {
+        path.push(name);
+
+        hasher.update((path.len() as u64).to_le_bytes());
+        for name in path.iter() {
+            hasher.update((name.len() as u64).to_le_bytes());
+            hasher.update(name.as_bytes());
+        }
+        hash_data_type(field.data_type(), hasher);
+        if include_nullability {
+            hasher.update([field.is_nullable() as u8]);
+        }
+        hash_metadata(field.metadata(), metadata_mode, hasher);
+
+        let children = child_fields(field.data_type());
+        hasher.update((children.len() as u64).to_le_bytes());
+        hash_schema_fields(children, include_nullability, metadata_mode, path, hasher);
+
+        path.pop();
+    }
+}
+
+// Returns nested fields of the type along with their names in the schema tree. Names of list
+// items and map keys and values vary between implementations and are not considered part
+// of the type, so such fields are anonymous.
+fn child_fields(data_type: &DataType) -> Vec<(&str, &Field)> {
+    match data_type {
+        DataType::Struct(fields) => fields
+            .iter()
+            .map(|f| (f.name().as_str(), f.as_ref()))
+            .collect(),
+        DataType::Union(fields, _) => fields
+            .iter()
+            .map(|(_, f)| (f.name().as_str(), f.as_ref()))
+            .collect(),
+        DataType::List(field)
+        | DataType::LargeList(field)
+        | DataType::ListView(field)
+        | DataType::LargeListView(field)
+        | DataType::FixedSizeList(field, _) => vec![("", field.as_ref())],
+        DataType::Map(entries_field, _) => match entries_field.data_type() {
+            DataType::Struct(entries_fields) => {
+                entries_fields.iter().map(|f| ("", f.as_ref())).collect()
+            }
+            _ => Vec::new(),
+        },
+        DataType::Dictionary(_, value_type) => child_fields(value_type),
+        DataType::RunEndEncoded(_, values_field) => child_fields(values_field.data_type()),
+        _ => Vec::new(),
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+// Entries are sorted by key so the result does not depend on the iteration order of the
+// map. Nothing is hashed when metadata is ignored to keep the default encoding unchanged.
+pub(crate) fn hash_metadata<H: crate::hasher::HashSink>(
+    metadata: &HashMap<String, String>,
+    metadata_mode: &MetadataMode,
+    hasher: &mut H,
+) {
+    let entries: BTreeMap<&String, &String> = match metadata_mode {
+        MetadataMode::None => return,
+        MetadataMode::All => metadata.iter().collect(),
+        MetadataMode::Keys(keys) => metadata.iter().filter(|(k, _)| keys.contains(k)).collect(),
+    };
+
+    hasher.update((entries.len() as u64).to_le_bytes());
+    for (key, value) in entries {
+        hasher.update((key.len() as u64).to_le_bytes());
+        hasher.update(key.as_bytes());
+        hasher.update((value.len() as u64).to_le_bytes());
+        hasher.update(value.as_bytes());
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+/// Checks that the data type (including all nested types) can be hashed
+pub fn check_data_type(data_type: &DataType) -> Result<(), DigestError> {
+    check_data_type_at(data_type, &mut Vec::new())
+}
+
+/// Checks that data types of all fields of the schema can be hashed
+pub fn check_schema(schema: &Schema) -> Result<(), DigestError> {
+    check_fields_at(schema.fields(), &mut Vec::new())
+}
+
+/// Checks that data with the byte order declared in IPC metadata can be hashed.
+///
+/// Arrays are hashed assuming they are in the native byte order of the host, while the
+/// `arrow-rs` IPC stream reader accepts data in any byte order without converting it, so
+/// data from untrusted sources should be checked before hashing. The byte order is
+/// declared in the schema message of a stream:
+///
+/// ```
+/// # fn check(message_bytes: &[u8]) -> Result<(), arrow_digest::DigestError> {
+/// let message = arrow::ipc::root_as_message(message_bytes).expect("Invalid IPC message");
+/// if let Some(schema) = message.header_as_schema() {
+///     arrow_digest::check_ipc_endianness(schema.endianness())?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "ipc")]
+pub fn check_ipc_endianness(endianness: arrow::ipc::Endianness) -> Result<(), DigestError> {
+    if endianness.equals_to_target_endianness() {
+        return Ok(());
+    }
+    Err(DigestError::NonNativeEndianness {
+        endianness: match endianness {
+            arrow::ipc::Endianness::Little => "little-endian".to_string(),
+            arrow::ipc::Endianness::Big => "big-endian".to_string(),
+            other => format!("unknown ({})", other.0),
+        },
+    })
+}
+
+// Checks that data type of a single field can be hashed, reporting the field in the path
+pub(crate) fn check_field(field: &FieldRef) -> Result<(), DigestError> {
+    check_fields_at([field], &mut Vec::new())
+}
+
+fn check_data_type_at<'a>(
+    data_type: &'a DataType,
+    path: &mut Vec<&'a str>,
+) -> Result<(), DigestError> {
+    let unsupported = |path: &Vec<&str>| DigestError::UnsupportedType {
+        data_type: data_type.clone(),
+        path: path.join("."),
+    };
+
+    match data_type {
+        DataType::List(field)
+        | DataType::LargeList(field)
+        | DataType::ListView(field)
+        | DataType::LargeListView(field)
+        | DataType::FixedSizeList(field, _) => check_fields_at([field], path),
+        DataType::Struct(fields) => check_fields_at(fields, path),
+        // Type IDs are mapped to field positions, so they have to be unique and non-negative
+        DataType::Union(fields, _) => {
+            let mut seen_type_ids = 0u128;
+            for (type_id, _) in fields.iter() {
+                if type_id < 0 || seen_type_ids & (1 << type_id) != 0 {
+                    return Err(unsupported(path));
+                }
+                seen_type_ids |= 1 << type_id;
+            }
+            check_fields_at(fields.iter().map(|(_, f)| f), path)
+        }
+        DataType::Map(entries_field, _) => match entries_field.data_type() {
+            DataType::Struct(entries_fields) if entries_fields.len() == 2 => {
+                path.push(entries_field.name());
+                check_fields_at(entries_fields, path)?;
+                path.pop();
+                Ok(())
+            }
+            _ => Err(unsupported(path)),
+        },
+        DataType::Dictionary(_, value_type) => check_data_type_at(value_type, path),
+        DataType::RunEndEncoded(run_ends_field, values_field) => match run_ends_field.data_type() {
+            DataType::Int16 | DataType::Int32 | DataType::Int64 => {
+                check_fields_at([values_field], path)
+            }
+            _ => Err(unsupported(path)),
+        },
+        _ => Ok(()),
+    }
+}
+
+fn check_fields_at<'a>(
+    fields: impl IntoIterator<Item = &'a FieldRef>,
+    path: &mut Vec<&'a str>,
+) -> Result<(), DigestError> {
+    for field in fields {
+        path.push(field.name());
+        check_data_type_at(field.data_type(), path)?;
+        path.pop();
+    }
+    Ok(())
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+// Tests
+/////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArrayDigest, ArrayDigestV0};
+    use arrow::array::new_empty_array;
+    use arrow::datatypes::{Fields, UnionFields, UnionMode};
+    use digest::Digest;
+    use sha3::Sha3_256;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_encode_data_type() {
+        assert_eq!(
+            encode_data_type(&DataType::Int32).unwrap(),
+            [
+                &(TypeID::Int as u16).to_le_bytes()[..],
+                &[1u8],
+                &32u64.to_le_bytes(),
+            ]
+            .concat(),
+        );
+
+        assert_eq!(
+            encode_data_type(&DataType::Duration(TimeUnit::Nanosecond)).unwrap(),
+            [
+                (TypeID::Duration as u16).to_le_bytes(),
+                (TimeUnitID::Nanosecond as u16).to_le_bytes(),
+            ]
+            .concat(),
+        );
+
+        // Equivalent encodings
+        assert_eq!(
+            encode_data_type(&DataType::Utf8),
+            encode_data_type(&DataType::Utf8View),
+        );
+        assert_eq!(
+            encode_data_type(&DataType::Utf8),
+            encode_data_type(&DataType::Dictionary(
+                Box::new(DataType::Int8),
+                Box::new(DataType::LargeUtf8)
+            )),
+        );
+        assert_ne!(
+            encode_data_type(&DataType::Utf8),
+            encode_data_type(&DataType::Binary),
+        );
+
+        // Encoding is the header of array digests
+        let mut hasher = Sha3_256::new();
+        hasher.update(encode_data_type(&DataType::Int32).unwrap());
+        assert_eq!(
+            hasher.finalize(),
+            ArrayDigestV0::<Sha3_256>::digest(&new_empty_array(&DataType::Int32)),
+        );
+
+        // Unsupported types are reported instead of being encoded
+        let map_type = DataType::Map(
+            Arc::new(Field::new("entries", DataType::Int32, false)),
+            false,
+        );
+        assert_eq!(
+            encode_data_type(&map_type),
+            Err(DigestError::UnsupportedType {
+                data_type: map_type.clone(),
+                path: String::new(),
+            }),
+        );
+    }
+
+    #[cfg(feature = "ipc")]
+    #[test]
+    fn test_check_ipc_endianness() {
+        use arrow::ipc::Endianness;
+
+        let (native, non_native) = if cfg!(target_endian = "little") {
+            (Endianness::Little, Endianness::Big)
+        } else {
+            (Endianness::Big, Endianness::Little)
+        };
+
+        assert_eq!(check_ipc_endianness(native), Ok(()));
+
+        let err = check_ipc_endianness(non_native).unwrap_err();
+        assert!(matches!(err, DigestError::NonNativeEndianness { .. }));
+        assert!(err.to_string().starts_with("Data in "));
+
+        assert_eq!(
+            check_ipc_endianness(Endianness(7)),
+            Err(DigestError::NonNativeEndianness {
+                endianness: "unknown (7)".to_string()
+            }),
+        );
+    }
+
+    #[test]
+    fn test_check_data_type() {
+        let bad_ree = DataType::RunEndEncoded(
+            Arc::new(Field::new("run_ends", DataType::Int8, false)),
+            Arc::new(Field::new("values", DataType::Utf8, true)),
+        );
+
+        assert_eq!(check_data_type(&DataType::Utf8), Ok(()));
+        assert_eq!(
+            check_data_type(&bad_ree),
+            Err(DigestError::UnsupportedType {
+                data_type: bad_ree.clone(),
+                path: String::new(),
+            }),
+        );
+
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new(
+                "b",
+                DataType::Struct(Fields::from(vec![Field::new(
+                    "c",
+                    DataType::new_list(bad_ree.clone(), true),
+                    true,
+                )])),
+                true,
+            ),
+        ]);
+
+        let err = check_schema(&schema).unwrap_err();
+        assert_eq!(
+            err,
+            DigestError::UnsupportedType {
+                data_type: bad_ree.clone(),
+                path: "b.c.item".to_string(),
+            },
+        );
+        assert_eq!(
+            err.to_string(),
+            format!("Type {bad_ree} of field 'b.c.item' is not supported"),
+        );
+
+        // Union type IDs must be unique and non-negative
+        for type_ids in [[0, 0], [-1, 0]] {
+            let bad_union = DataType::Union(
+                UnionFields::from_iter(type_ids.into_iter().zip([
+                    Arc::new(Field::new("a", DataType::Int32, true)),
+                    Arc::new(Field::new("b", DataType::Utf8, true)),
+                ])),
+                UnionMode::Dense,
+            );
+            assert_eq!(
+                check_data_type(&bad_union),
+                Err(DigestError::UnsupportedType {
+                    data_type: bad_union,
+                    path: String::new(),
+                }),
+            );
         }
-        DataType::Map(..) => unimplemented!(),
-        DataType::RunEndEncoded(..) => unimplemented!(),
+
+        let bad_map = DataType::Map(
+            Arc::new(Field::new("entries", DataType::Utf8, false)),
+            false,
+        );
+        assert_eq!(
+            check_schema(&Schema::new(vec![Field::new("m", bad_map.clone(), true)])),
+            Err(DigestError::UnsupportedType {
+                data_type: bad_map,
+                path: "m".to_string(),
+            }),
+        );
     }
 }
@@ -0,0 +1,33 @@
+use arrow::array::{ArrayRef, BooleanArray, Int32Array, ListArray, StringArray, StructArray};
+use arrow::datatypes::{DataType, Field, Int32Type};
+use arrow::record_batch::RecordBatch;
+use std::sync::Arc;
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+// Batch with nulls in primitive, boolean, list and struct columns, shared by tests of
+// hashers and adapters
+pub(crate) fn test_batch() -> RecordBatch {
+    let ints: ArrayRef = Arc::new(Int32Array::from(vec![Some(1), None, Some(3)]));
+    let bools: ArrayRef = Arc::new(BooleanArray::from(vec![Some(true), Some(false), None]));
+    let strs: ArrayRef = Arc::new(StringArray::from(vec![Some("a"), None, Some("c")]));
+    let list: ArrayRef = Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+        Some(vec![Some(1), None]),
+        None,
+        Some(vec![]),
+    ]));
+    let strct: ArrayRef = Arc::new(StructArray::from(vec![
+        (
+            Arc::new(Field::new("b", DataType::Boolean, true)),
+            bools.clone(),
+        ),
+        (Arc::new(Field::new("s", DataType::Utf8, true)), strs),
+    ]));
+    RecordBatch::try_from_iter([
+        ("ints", ints),
+        ("bools", bools),
+        ("list", list),
+        ("struct", strct),
+    ])
+    .unwrap()
+}
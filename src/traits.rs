@@ -6,11 +6,41 @@ use arrow::{
 };
 use digest::{Output, OutputSizeUser};
 
+use crate::DigestError;
+
 pub trait RecordDigest: OutputSizeUser {
     fn digest(batch: &RecordBatch) -> Output<Self>;
     fn new(schema: &Schema) -> Self;
     fn update(&mut self, batch: &RecordBatch);
     fn finalize(self) -> Output<Self>;
+
+    /// Same as [`RecordDigest::digest`] but returns an error instead of panicking
+    fn try_digest(batch: &RecordBatch) -> Result<Output<Self>, DigestError>
+    where
+        Self: Sized,
+    {
+        let mut d = Self::try_new(batch.schema().as_ref())?;
+        d.try_update(batch)?;
+        Ok(d.finalize())
+    }
+
+    /// Same as [`RecordDigest::new`] but returns an error instead of panicking
+    fn try_new(schema: &Schema) -> Result<Self, DigestError>
+    where
+        Self: Sized,
+    {
+        crate::check_schema(schema)?;
+        Ok(Self::new(schema))
+    }
+
+    /// Same as [`RecordDigest::update`] but returns an error instead of panicking. Digests of
+    /// this crate also reject batches that don't match the schema of the digest, which
+    /// [`RecordDigest::update`] checks only in debug builds.
+    fn try_update(&mut self, batch: &RecordBatch) -> Result<(), DigestError> {
+        crate::check_schema(batch.schema().as_ref())?;
+        self.update(batch);
+        Ok(())
+    }
 }
 
 pub trait ArrayDigest: OutputSizeUser {
@@ -18,4 +48,52 @@ pub trait ArrayDigest: OutputSizeUser {
     fn new(data_type: &DataType) -> Self;
     fn update(&mut self, array: &dyn Array, parent_null_bitmap: Option<&NullBuffer>);
     fn finalize(self) -> Output<Self>;
+
+    /// Same as [`ArrayDigest::digest`] but returns an error instead of panicking
+    fn try_digest(array: &dyn Array) -> Result<Output<Self>, DigestError>
+    where
+        Self: Sized,
+    {
+        let mut d = Self::try_new(array.data_type())?;
+        d.try_update(array, None)?;
+        Ok(d.finalize())
+    }
+
+    /// Same as [`ArrayDigest::new`] but returns an error instead of panicking
+    fn try_new(data_type: &DataType) -> Result<Self, DigestError>
+    where
+        Self: Sized,
+    {
+        crate::check_data_type(data_type)?;
+        Ok(Self::new(data_type))
+    }
+
+    /// Same as [`ArrayDigest::update`] but returns an error instead of panicking. Digests of
+    /// this crate also reject arrays whose type doesn't match the type of the digest.
+    fn try_update(
+        &mut self,
+        array: &dyn Array,
+        parent_null_bitmap: Option<&NullBuffer>,
+    ) -> Result<(), DigestError> {
+        check_array(array, parent_null_bitmap)?;
+        self.update(array, parent_null_bitmap);
+        Ok(())
+    }
+}
+
+// Validates arguments of `try_update()` of array digests
+pub(crate) fn check_array(
+    array: &dyn Array,
+    parent_null_bitmap: Option<&NullBuffer>,
+) -> Result<(), DigestError> {
+    crate::check_data_type(array.data_type())?;
+    if let Some(parent_null_bitmap) = parent_null_bitmap
+        && parent_null_bitmap.len() != array.len()
+    {
+        return Err(DigestError::NullBitmapLengthMismatch {
+            expected: array.len(),
+            actual: parent_null_bitmap.len(),
+        });
+    }
+    Ok(())
 }
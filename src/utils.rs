@@ -1,4 +1,9 @@
-use arrow::buffer::{BooleanBuffer, NullBuffer, buffer_bin_or};
+use arrow::{
+    array::{Array, ArrayRef, StructArray},
+    buffer::{BooleanBuffer, NullBuffer, buffer_bin_and, buffer_bin_or},
+    datatypes::{DataType, Field, FieldRef, Fields},
+};
+use std::borrow::Cow;
 
 /////////////////////////////////////////////////////////////////////////////////////////
 
@@ -11,8 +16,9 @@ pub(crate) fn combine_null_buffers(a: &NullBuffer, b: &NullBuffer) -> NullBuffer
         b.len()
     );
 
-    // In NullBuffer `1` stands for valid and `0` for null
-    let buffer = buffer_bin_or(a.buffer(), a.offset(), b.buffer(), b.offset(), a.len());
+    // In NullBuffer `1` stands for valid and `0` for null, so value is valid only when
+    // it's valid in both buffers
+    let buffer = buffer_bin_and(a.buffer(), a.offset(), b.buffer(), b.offset(), a.len());
 
     NullBuffer::new(BooleanBuffer::new(buffer, 0, a.len()))
 }
@@ -22,12 +28,40 @@ pub(crate) fn combine_null_buffers(a: &NullBuffer, b: &NullBuffer) -> NullBuffer
 pub(crate) fn maybe_combine_null_buffers<'a>(
     a: Option<&'a NullBuffer>,
     b: Option<&'a NullBuffer>,
+) -> CombinedNullBuffer<'a> {
+    // Buffers without any nulls are equivalent to no buffer at all
+    let a = a.filter(|a| a.null_count() != 0);
+    let b = b.filter(|b| b.null_count() != 0);
+
+    match (a, b) {
+        (None, None) => CombinedNullBuffer::None,
+        (Some(a), None) | (None, Some(a)) => CombinedNullBuffer::Borrowed(a),
+        (Some(a), Some(b)) => CombinedNullBuffer::Owned(combine_null_buffers(a, b)),
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+// Combination of null buffers used by V0 and kept as is for compatibility with existing
+// hashes: a value is null only when it's null in both buffers, and the parent buffer is
+// discarded when the child has a buffer without nulls
+pub(crate) fn maybe_combine_null_buffers_v0<'a>(
+    a: Option<&'a NullBuffer>,
+    b: Option<&'a NullBuffer>,
 ) -> CombinedNullBuffer<'a> {
     match (a, b) {
         (None, Some(b)) if b.null_count() != 0 => CombinedNullBuffer::Borrowed(b),
         (Some(a), None) if a.null_count() != 0 => CombinedNullBuffer::Borrowed(a),
         (Some(a), Some(b)) if a.null_count() != 0 && b.null_count() != 0 => {
-            CombinedNullBuffer::Owned(combine_null_buffers(a, b))
+            assert_eq!(
+                a.len(),
+                b.len(),
+                "Attempting to combine buffers of different size {} != {}",
+                a.len(),
+                b.len()
+            );
+            let buffer = buffer_bin_or(a.buffer(), a.offset(), b.buffer(), b.offset(), a.len());
+            CombinedNullBuffer::Owned(NullBuffer::new(BooleanBuffer::new(buffer, 0, a.len())))
         }
         _ => CombinedNullBuffer::None,
     }
@@ -51,3 +85,243 @@ impl<'a> CombinedNullBuffer<'a> {
         }
     }
 }
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+// Visits all fields in the depth-first order, including the struct fields themselves
+pub(crate) fn walk_nested_fields(
+    fields: &Fields,
+    level: usize,
+    fun: &mut impl FnMut(&Field, usize),
+) {
+    for field in fields {
+        match field.data_type() {
+            DataType::Struct(nested_fields) => {
+                fun(field, level);
+                walk_nested_fields(nested_fields, level + 1, fun);
+            }
+            _ => fun(field, level),
+        }
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+// V0 flattens plain structs into leaf columns, while dictionary or run-end encoded structs
+// are hashed as a single column, so within a struct (or a record batch) one can't replace the
+// other even though they are hashed identically elsewhere. Expects types with equal encodings,
+// `flattened` tells whether the types are of fields of a struct or of a record batch.
+pub(crate) fn flattens_identically(
+    expected: &DataType,
+    actual: &DataType,
+    flattened: bool,
+) -> bool {
+    fn decoded(data_type: &DataType) -> &DataType {
+        match data_type {
+            DataType::Dictionary(_, values) => decoded(values),
+            DataType::RunEndEncoded(_, values) => decoded(values.data_type()),
+            _ => data_type,
+        }
+    }
+
+    let (expected, actual) = if flattened {
+        (expected, actual)
+    } else {
+        (decoded(expected), decoded(actual))
+    };
+
+    match (expected, actual) {
+        (DataType::Struct(expected_fields), DataType::Struct(actual_fields)) => expected_fields
+            .iter()
+            .zip(actual_fields.iter())
+            .all(|(e, a)| flattens_identically(e.data_type(), a.data_type(), true)),
+        (DataType::Struct(_), _) | (_, DataType::Struct(_)) => false,
+        (DataType::Map(expected_entries, _), DataType::Map(actual_entries, _)) => {
+            match (expected_entries.data_type(), actual_entries.data_type()) {
+                (DataType::Struct(expected_fields), DataType::Struct(actual_fields)) => {
+                    expected_fields
+                        .iter()
+                        .zip(actual_fields.iter())
+                        .all(|(e, a)| flattens_identically(e.data_type(), a.data_type(), false))
+                }
+                _ => true,
+            }
+        }
+        (DataType::Union(expected_fields, _), DataType::Union(actual_fields, _)) => expected_fields
+            .iter()
+            .zip(actual_fields.iter())
+            .all(|((_, e), (_, a))| flattens_identically(e.data_type(), a.data_type(), false)),
+        (DataType::Dictionary(..) | DataType::RunEndEncoded(..), _)
+        | (_, DataType::Dictionary(..) | DataType::RunEndEncoded(..)) => {
+            flattens_identically(expected, actual, false)
+        }
+        _ => match (list_item(expected), list_item(actual)) {
+            (Some(e), Some(a)) => flattens_identically(e.data_type(), a.data_type(), false),
+            _ => true,
+        },
+    }
+}
+
+fn list_item(data_type: &DataType) -> Option<&FieldRef> {
+    match data_type {
+        DataType::List(item)
+        | DataType::LargeList(item)
+        | DataType::ListView(item)
+        | DataType::LargeListView(item)
+        | DataType::FixedSizeList(item, _) => Some(item),
+        _ => None,
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+// Visits all leaf (non-struct) columns in the depth-first order, passing the combined null
+// bitmap of all parent structs along with every column (V0 only). Legacy combination of
+// nulls is used only for top-level columns of record batches to keep existing hashes.
+pub(crate) fn walk_nested_columns<'a>(
+    arrays: impl Iterator<Item = &'a ArrayRef>,
+    parent_null_bitmap: Option<&NullBuffer>,
+    legacy_nulls: bool,
+    fun: &mut impl FnMut(&ArrayRef, Option<&NullBuffer>),
+) {
+    for array in arrays {
+        match array.data_type() {
+            DataType::Struct(_) => {
+                let array = array.as_any().downcast_ref::<StructArray>().unwrap();
+                let array_data = array.to_data();
+
+                let combined_nulls = if legacy_nulls {
+                    maybe_combine_null_buffers_v0(parent_null_bitmap, array_data.nulls())
+                } else {
+                    maybe_combine_null_buffers(parent_null_bitmap, array_data.nulls())
+                };
+
+                for i in 0..array.num_columns() {
+                    walk_nested_columns(
+                        [array.column(i)].into_iter(),
+                        combined_nulls.as_option(),
+                        legacy_nulls,
+                        fun,
+                    );
+                }
+            }
+            _ => fun(array, parent_null_bitmap),
+        }
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+// Hashes a stream of bits packed into bytes in LSB order followed by the total number of
+// bits. Bits are buffered into fixed-size blocks, so the result does not depend on how many
+// bits were appended at a time.
+pub(crate) struct BitmapDigest<H: crate::hasher::HashSink> {
+    hasher: H,
+    block: Vec<u8>,
+    // Bits that don't yet form a complete word
+    acc: u64,
+    acc_len: usize,
+    len: u64,
+}
+
+impl<H: crate::hasher::HashSink> BitmapDigest<H> {
+    const BLOCK_SIZE: usize = 8 * 1024;
+
+    pub fn new(hasher: H) -> Self {
+        Self {
+            hasher,
+            block: Vec::with_capacity(Self::BLOCK_SIZE),
+            acc: 0,
+            acc_len: 0,
+            len: 0,
+        }
+    }
+
+    pub fn append(&mut self, bit: bool) {
+        self.append_bits(bit as u64, 1);
+    }
+
+    pub fn append_n(&mut self, mut n: usize, bit: bool) {
+        let word = if bit { u64::MAX } else { 0 };
+        while n >= 64 {
+            self.append_bits(word, 64);
+            n -= 64;
+        }
+        if n != 0 {
+            self.append_bits(word, n);
+        }
+    }
+
+    pub fn append_bitmap(&mut self, bitmap: &BooleanBuffer) {
+        let chunks = bitmap.bit_chunks();
+        for word in chunks.iter() {
+            self.append_bits(word, 64);
+        }
+        if chunks.remainder_len() != 0 {
+            self.append_bits(chunks.remainder_bits(), chunks.remainder_len());
+        }
+    }
+
+    // Appends `count` (1..=64) lowest bits of the `bits` word
+    #[inline]
+    fn append_bits(&mut self, bits: u64, count: usize) {
+        let bits = if count == 64 {
+            bits
+        } else {
+            bits & ((1u64 << count) - 1)
+        };
+
+        self.len += count as u64;
+        self.acc |= bits << self.acc_len;
+
+        let acc_len = self.acc_len + count;
+        if acc_len < 64 {
+            self.acc_len = acc_len;
+            return;
+        }
+
+        self.block.extend_from_slice(&self.acc.to_le_bytes());
+        if self.block.len() >= Self::BLOCK_SIZE {
+            self.hasher.update(&self.block);
+            self.block.clear();
+        }
+
+        // Carry over the bits that did not fit into the word
+        self.acc = if self.acc_len == 0 {
+            0
+        } else {
+            bits >> (64 - self.acc_len)
+        };
+        self.acc_len = acc_len - 64;
+    }
+
+    pub fn finalize(mut self) -> H::Output {
+        let tail_len = self.acc_len.div_ceil(8);
+        self.block
+            .extend_from_slice(&self.acc.to_le_bytes()[..tail_len]);
+        self.hasher.update(&self.block);
+        self.hasher.update(self.len.to_le_bytes());
+        self.hasher.finalize()
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+// Values of fixed-size types are hashed in little-endian byte order, so on big-endian hosts
+// every native word of the buffer is byte-swapped
+pub(crate) fn to_little_endian(buf: &[u8], item_size: usize) -> Cow<'_, [u8]> {
+    if cfg!(target_endian = "little") || item_size == 1 {
+        return Cow::Borrowed(buf);
+    }
+    Cow::Owned(swap_byte_order(buf, item_size))
+}
+
+// Reverses bytes of every native word of the buffer. Types wider than 128 bits (i.e. i256)
+// are stored as multiple 128-bit native words in little-endian word order.
+pub(crate) fn swap_byte_order(buf: &[u8], item_size: usize) -> Vec<u8> {
+    let mut buf = buf.to_vec();
+    for word in buf.chunks_exact_mut(item_size.min(16)) {
+        word.reverse();
+    }
+    buf
+}
@@ -0,0 +1,926 @@
+//! Verifies the digests against the test vectors in `tests/data/test_vectors.json`.
+//!
+//! Every vector contains:
+//! - `input_ipc` - hex-encoded Arrow IPC stream, every batch of which is fed into the digest
+//!   separately (`ArrayDigest*` vectors hash the only column of the stream)
+//! - `slice` *(optional)* - `offset` and `length` of the slice of every decoded batch that is
+//!   hashed instead of the whole batch, as IPC doesn't preserve offsets of sliced arrays
+//! - `expected_stream` - hex-encoded canonical bytes fed into the top-level hasher (digests of
+//!   nested hashers are computed using SHA3-256 too)
+//! - `expected_nested_streams` - hex-encoded canonical bytes fed into every nested hasher
+//!   (validity bitmaps, fields, items, etc.) keyed by the path of the hasher. The path is a
+//!   dot-separated list of positions of digests among the nested digests fed into a hasher,
+//!   e.g. `1.0` is the hasher whose digest is the first nested digest fed into the hasher
+//!   of the second nested digest fed into the top-level hasher
+//! - `expected_sha3_256` - hex-encoded final digest, i.e. SHA3-256 of `expected_stream`
+//!
+//! To regenerate the vectors after an intentional change of the encoding run:
+//!
+//! ```sh
+//! cargo test --test test_vectors -- --ignored generate_test_vectors
+//! ```
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use arrow::array::*;
+use arrow::buffer::{NullBuffer, OffsetBuffer, ScalarBuffer};
+use arrow::datatypes::*;
+use arrow::ipc::reader::StreamReader;
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
+use arrow_digest::{
+    ArrayDigest, ArrayDigestV0, ArrayDigestV1, RecordDigest, RecordDigestV0, RecordDigestV1,
+};
+use digest::{FixedOutput, HashMarker, Output, OutputSizeUser, Update};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TestVector {
+    name: String,
+    digest: DigestKind,
+    input_ipc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    slice: Option<Slice>,
+    expected_stream: String,
+    expected_nested_streams: BTreeMap<String, String>,
+    expected_sha3_256: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Slice {
+    offset: usize,
+    length: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+enum DigestKind {
+    ArrayDigestV0,
+    ArrayDigestV1,
+    RecordDigestV0,
+    RecordDigestV1,
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+thread_local! {
+    static FINALIZED_STREAMS: RefCell<Vec<FinalizedStream>> = const { RefCell::new(Vec::new()) };
+}
+
+struct FinalizedStream {
+    stream: Vec<u8>,
+    digest: Vec<u8>,
+}
+
+// SHA3-256 that records all bytes it receives. The stream of the top-level hasher is the
+// last one to be finalized, as nested hashers are finalized before their digests are fed
+// into their parents.
+#[derive(Default, Clone)]
+struct RecordingSha3 {
+    inner: Sha3_256,
+    stream: Vec<u8>,
+}
+
+impl HashMarker for RecordingSha3 {}
+
+impl OutputSizeUser for RecordingSha3 {
+    type OutputSize = <Sha3_256 as OutputSizeUser>::OutputSize;
+}
+
+impl Update for RecordingSha3 {
+    fn update(&mut self, data: &[u8]) {
+        Update::update(&mut self.inner, data);
+        self.stream.extend_from_slice(data);
+    }
+}
+
+impl FixedOutput for RecordingSha3 {
+    fn finalize_into(self, out: &mut Output<Self>) {
+        FixedOutput::finalize_into(self.inner, out);
+        FINALIZED_STREAMS.with(|s| {
+            s.borrow_mut().push(FinalizedStream {
+                stream: self.stream,
+                digest: out.to_vec(),
+            })
+        });
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+fn compute<Dig: Digest>(kind: DigestKind, batches: &[RecordBatch]) -> Output<Dig> {
+    fn array_digest<D: ArrayDigest>(batches: &[RecordBatch]) -> Output<D> {
+        let mut d = D::new(batches[0].schema().field(0).data_type());
+        for batch in batches {
+            d.update(batch.column(0).as_ref(), None);
+        }
+        d.finalize()
+    }
+
+    fn record_digest<D: RecordDigest>(batches: &[RecordBatch]) -> Output<D> {
+        let mut d = D::new(&batches[0].schema());
+        for batch in batches {
+            d.update(batch);
+        }
+        d.finalize()
+    }
+
+    match kind {
+        DigestKind::ArrayDigestV0 => array_digest::<ArrayDigestV0<Dig>>(batches),
+        DigestKind::ArrayDigestV1 => array_digest::<ArrayDigestV1<Dig>>(batches),
+        DigestKind::RecordDigestV0 => record_digest::<RecordDigestV0<Dig>>(batches),
+        DigestKind::RecordDigestV1 => record_digest::<RecordDigestV1<Dig>>(batches),
+    }
+}
+
+// Returns the canonical byte stream of the top-level hasher and the final digest
+fn compute_recorded(kind: DigestKind, batches: &[RecordBatch]) -> (Streams, Vec<u8>) {
+    FINALIZED_STREAMS.with(|s| s.borrow_mut().clear());
+    let digest = compute::<RecordingSha3>(kind, batches);
+    let finalized = FINALIZED_STREAMS.with(|s| std::mem::take(&mut *s.borrow_mut()));
+    (nest_streams(finalized), digest.to_vec())
+}
+
+// Streams of the top-level hasher (with an empty path) and of all nested hashers keyed by
+// their paths
+type Streams = BTreeMap<String, Vec<u8>>;
+
+// Restores the tree of hashers from the order of finalization: every hasher is the parent
+// of the previously finalized hashers whose digests it received and that don't have a
+// parent yet
+fn nest_streams(finalized: Vec<FinalizedStream>) -> Streams {
+    struct Node {
+        stream: Vec<u8>,
+        digest: Vec<u8>,
+        children: Vec<Node>,
+    }
+
+    fn find(haystack: &[u8], needle: &[u8], taken: &[usize]) -> Option<usize> {
+        (0..=haystack.len().saturating_sub(needle.len()))
+            .find(|i| haystack[*i..].starts_with(needle) && !taken.contains(i))
+    }
+
+    fn flatten(node: Node, path: String, streams: &mut Streams) {
+        for (i, child) in node.children.into_iter().enumerate() {
+            let child_path = if path.is_empty() {
+                i.to_string()
+            } else {
+                format!("{path}.{i}")
+            };
+            flatten(child, child_path, streams);
+        }
+        streams.insert(path, node.stream);
+    }
+
+    let mut orphans: Vec<Node> = Vec::new();
+    for FinalizedStream { stream, digest } in finalized {
+        let mut children = Vec::new();
+        let mut taken = Vec::new();
+        for orphan in std::mem::take(&mut orphans) {
+            match find(&stream, &orphan.digest, &taken) {
+                Some(pos) => {
+                    taken.push(pos);
+                    children.push((pos, orphan));
+                }
+                None => orphans.push(orphan),
+            }
+        }
+        children.sort_by_key(|(pos, _)| *pos);
+        orphans.push(Node {
+            stream,
+            digest,
+            children: children.into_iter().map(|(_, child)| child).collect(),
+        });
+    }
+
+    assert_eq!(orphans.len(), 1, "Digests of some hashers were not used");
+    let mut streams = Streams::new();
+    flatten(orphans.pop().unwrap(), String::new(), &mut streams);
+    streams
+}
+
+fn hex_nested_streams(streams: &Streams) -> BTreeMap<String, String> {
+    streams
+        .iter()
+        .filter(|(path, _)| !path.is_empty())
+        .map(|(path, stream)| (path.clone(), hex::encode(stream)))
+        .collect()
+}
+
+fn read_ipc(data: &[u8]) -> Vec<RecordBatch> {
+    // Stream starts with the schema message prefixed by a continuation marker and its length
+    #[cfg(feature = "ipc")]
+    {
+        let len = i32::from_le_bytes(data[4..8].try_into().unwrap()) as usize;
+        let message = arrow::ipc::root_as_message(&data[8..8 + len]).unwrap();
+        arrow_digest::check_ipc_endianness(message.header_as_schema().unwrap().endianness())
+            .unwrap();
+    }
+
+    StreamReader::try_new(data, None)
+        .unwrap()
+        .map(|b| b.unwrap())
+        .collect()
+}
+
+fn apply_slice(batches: &[RecordBatch], slice: Option<Slice>) -> Vec<RecordBatch> {
+    batches
+        .iter()
+        .map(|b| match slice {
+            None => b.clone(),
+            Some(Slice { offset, length }) => b.slice(offset, length),
+        })
+        .collect()
+}
+
+fn write_ipc(batches: &[RecordBatch]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    let mut writer = StreamWriter::try_new(&mut buf, &batches[0].schema()).unwrap();
+    for batch in batches {
+        writer.write(batch).unwrap();
+    }
+    writer.finish().unwrap();
+    drop(writer);
+    buf
+}
+
+fn vectors_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/test_vectors.json")
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn test_vectors() {
+    let vectors: Vec<TestVector> =
+        serde_json::from_str(&std::fs::read_to_string(vectors_path()).unwrap()).unwrap();
+
+    assert!(!vectors.is_empty());
+
+    for vector in vectors {
+        let batches = apply_slice(
+            &read_ipc(&hex::decode(&vector.input_ipc).unwrap()),
+            vector.slice,
+        );
+        let expected_stream = hex::decode(&vector.expected_stream).unwrap();
+        let expected_digest = hex::decode(&vector.expected_sha3_256).unwrap();
+
+        // The vector must be self-consistent
+        assert_eq!(
+            Sha3_256::digest(&expected_stream).to_vec(),
+            expected_digest,
+            "Inconsistent vector {}",
+            vector.name
+        );
+
+        let (streams, digest) = compute_recorded(vector.digest, &batches);
+        assert_eq!(
+            hex::encode(&streams[""]),
+            vector.expected_stream,
+            "Stream mismatch in vector {}",
+            vector.name
+        );
+        assert_eq!(
+            hex_nested_streams(&streams),
+            vector.expected_nested_streams,
+            "Nested stream mismatch in vector {}",
+            vector.name
+        );
+        assert_eq!(
+            hex::encode(&digest),
+            vector.expected_sha3_256,
+            "Digest mismatch in vector {}",
+            vector.name
+        );
+
+        assert_eq!(
+            compute::<Sha3_256>(vector.digest, &batches).to_vec(),
+            expected_digest,
+            "Digest mismatch in vector {}",
+            vector.name
+        );
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////
+// Generation
+/////////////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+#[ignore = "regenerates test vectors"]
+fn generate_test_vectors() {
+    let mut vectors = Vec::new();
+
+    for (name, batches, slice) in array_inputs() {
+        for kind in [DigestKind::ArrayDigestV0, DigestKind::ArrayDigestV1] {
+            vectors.push(make_vector(&name, kind, &batches, slice));
+        }
+    }
+
+    for (name, batches, slice) in record_inputs() {
+        for kind in [DigestKind::RecordDigestV0, DigestKind::RecordDigestV1] {
+            vectors.push(make_vector(&name, kind, &batches, slice));
+        }
+    }
+
+    let mut json = serde_json::to_string_pretty(&vectors).unwrap();
+    json.push('\n');
+    std::fs::create_dir_all(vectors_path().parent().unwrap()).unwrap();
+    std::fs::write(vectors_path(), json).unwrap();
+}
+
+fn make_vector(
+    name: &str,
+    kind: DigestKind,
+    batches: &[RecordBatch],
+    slice: Option<Slice>,
+) -> TestVector {
+    let ipc = write_ipc(batches);
+
+    // Digest must survive the IPC round trip. Slices are taken after decoding, so that
+    // the digest sees arrays with non-zero offsets.
+    let (streams, digest) = compute_recorded(kind, &apply_slice(batches, slice));
+    assert_eq!(
+        compute_recorded(kind, &apply_slice(&read_ipc(&ipc), slice)),
+        (streams.clone(), digest.clone()),
+        "IPC round trip changes digest of {name} {kind:?}"
+    );
+
+    TestVector {
+        name: name.to_string(),
+        digest: kind,
+        input_ipc: hex::encode(ipc),
+        slice,
+        expected_stream: hex::encode(&streams[""]),
+        expected_nested_streams: hex_nested_streams(&streams),
+        expected_sha3_256: hex::encode(digest),
+    }
+}
+
+// Wraps every array into a single-column batch
+fn column_batches(arrays: Vec<ArrayRef>) -> Vec<RecordBatch> {
+    let schema = Arc::new(Schema::new(vec![Field::new(
+        "value",
+        arrays[0].data_type().clone(),
+        true,
+    )]));
+    arrays
+        .into_iter()
+        .map(|a| RecordBatch::try_new(schema.clone(), vec![a]).unwrap())
+        .collect()
+}
+
+fn array_inputs() -> Vec<(String, Vec<RecordBatch>, Option<Slice>)> {
+    let mut inputs: Vec<(&str, Vec<ArrayRef>)> = vec![
+        // Fixed size types
+        ("null", vec![Arc::new(NullArray::new(3))]),
+        (
+            "bool",
+            vec![Arc::new(BooleanArray::from(vec![
+                Some(true),
+                None,
+                Some(false),
+                Some(true),
+            ]))],
+        ),
+        (
+            "int8",
+            vec![Arc::new(Int8Array::from(vec![Some(0), None, Some(-1)]))],
+        ),
+        (
+            "int16",
+            vec![Arc::new(Int16Array::from(vec![Some(1), None, Some(-300)]))],
+        ),
+        ("int32", vec![Arc::new(Int32Array::from(vec![1, 2, 3]))]),
+        (
+            "int64",
+            vec![Arc::new(Int64Array::from(vec![Some(i64::MIN), None]))],
+        ),
+        ("uint8", vec![Arc::new(UInt8Array::from(vec![0, 255]))]),
+        (
+            "uint16",
+            vec![Arc::new(UInt16Array::from(vec![Some(65535), None]))],
+        ),
+        ("uint32", vec![Arc::new(UInt32Array::from(vec![1, 2]))]),
+        ("uint64", vec![Arc::new(UInt64Array::from(vec![u64::MAX]))]),
+        (
+            "float16",
+            vec![
+                arrow::compute::cast(
+                    &Float32Array::from(vec![Some(1.5), None]),
+                    &DataType::Float16,
+                )
+                .unwrap(),
+            ],
+        ),
+        (
+            "float32",
+            vec![Arc::new(Float32Array::from(vec![
+                Some(1.5),
+                None,
+                Some(-0.0),
+            ]))],
+        ),
+        (
+            "float64",
+            vec![Arc::new(Float64Array::from(vec![3.25, f64::NAN]))],
+        ),
+        (
+            "decimal32",
+            vec![Arc::new(
+                Decimal32Array::from(vec![Some(12345), None])
+                    .with_precision_and_scale(9, 2)
+                    .unwrap(),
+            )],
+        ),
+        (
+            "decimal64",
+            vec![Arc::new(
+                Decimal64Array::from(vec![Some(-12345), None])
+                    .with_precision_and_scale(18, 3)
+                    .unwrap(),
+            )],
+        ),
+        (
+            "decimal128",
+            vec![Arc::new(
+                Decimal128Array::from(vec![Some(i128::MAX), None, Some(-1)])
+                    .with_precision_and_scale(38, 10)
+                    .unwrap(),
+            )],
+        ),
+        (
+            "decimal256",
+            vec![Arc::new(
+                Decimal256Array::from(vec![Some(i256::from_parts(1, -2)), None])
+                    .with_precision_and_scale(76, 0)
+                    .unwrap(),
+            )],
+        ),
+        (
+            "date32",
+            vec![Arc::new(Date32Array::from(vec![Some(19000), None]))],
+        ),
+        (
+            "date64",
+            vec![Arc::new(Date64Array::from(vec![1_600_000_000_000]))],
+        ),
+        (
+            "time32_second",
+            vec![Arc::new(Time32SecondArray::from(vec![3600]))],
+        ),
+        (
+            "time32_millisecond",
+            vec![Arc::new(Time32MillisecondArray::from(vec![Some(1), None]))],
+        ),
+        (
+            "time64_microsecond",
+            vec![Arc::new(Time64MicrosecondArray::from(vec![1]))],
+        ),
+        (
+            "time64_nanosecond",
+            vec![Arc::new(Time64NanosecondArray::from(vec![1]))],
+        ),
+        (
+            "timestamp_millisecond",
+            vec![Arc::new(TimestampMillisecondArray::from(vec![
+                Some(1_600_000_000_000),
+                None,
+            ]))],
+        ),
+        (
+            "timestamp_nanosecond_utc",
+            vec![Arc::new(
+                TimestampNanosecondArray::from(vec![1_600_000_000_000_000_000])
+                    .with_timezone("UTC"),
+            )],
+        ),
+        (
+            "duration_second",
+            vec![Arc::new(DurationSecondArray::from(vec![Some(60), None]))],
+        ),
+        (
+            "duration_nanosecond",
+            vec![Arc::new(DurationNanosecondArray::from(vec![1]))],
+        ),
+        (
+            "interval_year_month",
+            vec![Arc::new(IntervalYearMonthArray::from(vec![Some(13), None]))],
+        ),
+        (
+            "interval_day_time",
+            vec![Arc::new(IntervalDayTimeArray::from(vec![
+                IntervalDayTime::new(1, 2),
+            ]))],
+        ),
+        (
+            "interval_month_day_nano",
+            vec![Arc::new(IntervalMonthDayNanoArray::from(vec![
+                Some(IntervalMonthDayNano::new(1, 2, 3)),
+                None,
+            ]))],
+        ),
+        // Variable size types
+        (
+            "utf8",
+            vec![Arc::new(StringArray::from(vec![
+                Some("foo"),
+                None,
+                Some(""),
+            ]))],
+        ),
+        (
+            "large_utf8",
+            vec![Arc::new(LargeStringArray::from(vec!["foo", "bar"]))],
+        ),
+        (
+            "utf8_view",
+            vec![Arc::new(StringViewArray::from(vec![
+                Some("a string longer than twelve bytes"),
+                None,
+            ]))],
+        ),
+        (
+            "binary",
+            vec![Arc::new(BinaryArray::from(vec![
+                Some(b"\x00\x01".as_ref()),
+                None,
+            ]))],
+        ),
+        (
+            "large_binary",
+            vec![Arc::new(LargeBinaryArray::from(vec![b"\x02".as_ref()]))],
+        ),
+        (
+            "fixed_size_binary",
+            vec![Arc::new(
+                FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+                    vec![Some(b"ab".as_ref()), None].into_iter(),
+                    2,
+                )
+                .unwrap(),
+            )],
+        ),
+        (
+            "binary_view",
+            vec![Arc::new(BinaryViewArray::from(vec![
+                Some(b"\x03".as_ref()),
+                None,
+            ]))],
+        ),
+        // Nulls, slices and splits
+        (
+            "utf8_all_null",
+            vec![Arc::new(StringArray::from(vec![None::<&str>; 8]))],
+        ),
+        (
+            "utf8_split",
+            vec![
+                Arc::new(StringArray::from(vec![Some("a"), None])),
+                Arc::new(StringArray::from(vec![Some("bc")])),
+                Arc::new(StringArray::from(vec![None, Some("")])),
+            ],
+        ),
+        // Nested types
+        (
+            "list",
+            vec![Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(
+                vec![Some(vec![Some(1), None]), None, Some(vec![])],
+            ))],
+        ),
+        (
+            "large_list_split",
+            vec![
+                Arc::new(LargeListArray::from_iter_primitive::<Int32Type, _, _>(
+                    vec![Some(vec![Some(1)])],
+                )),
+                Arc::new(LargeListArray::from_iter_primitive::<Int32Type, _, _>(
+                    vec![None, Some(vec![Some(2), Some(3)])],
+                )),
+            ],
+        ),
+        (
+            "fixed_size_list",
+            vec![Arc::new(FixedSizeListArray::from_iter_primitive::<
+                Int32Type,
+                _,
+                _,
+            >(
+                vec![Some(vec![Some(1), Some(2)]), None], 2
+            ))],
+        ),
+    ];
+
+    // Views are out of order and overlap
+    let list_view_values: ArrayRef = Arc::new(Int32Array::from(vec![Some(1), Some(2), None]));
+    inputs.push((
+        "list_view",
+        vec![Arc::new(ListViewArray::new(
+            Arc::new(Field::new_list_field(DataType::Int32, true)),
+            ScalarBuffer::from(vec![1, 0, 0, 3]),
+            ScalarBuffer::from(vec![2, 2, 0, 0]),
+            list_view_values.clone(),
+            Some(NullBuffer::from(vec![true, true, false, true])),
+        ))],
+    ));
+    inputs.push((
+        "large_list_view",
+        vec![Arc::new(LargeListViewArray::new(
+            Arc::new(Field::new_list_field(DataType::Int32, true)),
+            ScalarBuffer::from(vec![1i64, 0, 0, 3]),
+            ScalarBuffer::from(vec![2i64, 2, 0, 0]),
+            list_view_values,
+            Some(NullBuffer::from(vec![true, true, false, true])),
+        ))],
+    ));
+
+    let struct_fields = Fields::from(vec![
+        Field::new("a", DataType::Int32, true),
+        Field::new("b", DataType::Utf8, true),
+    ]);
+    let struct_array = StructArray::new(
+        struct_fields.clone(),
+        vec![
+            Arc::new(Int32Array::from(vec![Some(1), None, Some(3)])),
+            Arc::new(StringArray::from(vec![Some("x"), Some("y"), None])),
+        ],
+        Some(NullBuffer::from(vec![true, true, false])),
+    );
+    inputs.push(("struct", vec![Arc::new(struct_array.clone())]));
+
+    let outer_fields = Fields::from(vec![
+        Field::new("s", DataType::Struct(struct_fields.clone()), true),
+        Field::new("c", DataType::Boolean, true),
+    ]);
+    inputs.push((
+        "struct_nested",
+        vec![Arc::new(StructArray::new(
+            outer_fields,
+            vec![
+                Arc::new(struct_array.clone()),
+                Arc::new(BooleanArray::from(vec![Some(true), None, Some(false)])),
+            ],
+            Some(NullBuffer::from(vec![false, true, true])),
+        ))],
+    ));
+    inputs.push((
+        "list_of_structs",
+        vec![Arc::new(ListArray::new(
+            Arc::new(Field::new_list_field(
+                DataType::Struct(struct_fields.clone()),
+                true,
+            )),
+            OffsetBuffer::from_lengths([2, 0, 1]),
+            Arc::new(struct_array.clone()),
+            Some(NullBuffer::from(vec![true, false, true])),
+        ))],
+    ));
+
+    let map_fields = Fields::from(vec![
+        Field::new("keys", DataType::Utf8, false),
+        Field::new("values", DataType::Int32, true),
+    ]);
+    inputs.push((
+        "map",
+        vec![Arc::new(MapArray::new(
+            Arc::new(Field::new(
+                "entries",
+                DataType::Struct(map_fields.clone()),
+                false,
+            )),
+            OffsetBuffer::from_lengths([2, 0, 1]),
+            StructArray::new(
+                map_fields,
+                vec![
+                    Arc::new(StringArray::from(vec!["k1", "k2", "k3"])),
+                    Arc::new(Int32Array::from(vec![Some(1), None, Some(3)])),
+                ],
+                None,
+            ),
+            Some(NullBuffer::from(vec![true, false, true])),
+            false,
+        ))],
+    ));
+
+    let union_fields = UnionFields::try_new(
+        vec![0, 5],
+        vec![
+            Field::new("i", DataType::Int32, true),
+            Field::new("s", DataType::Utf8, true),
+        ],
+    )
+    .unwrap();
+    inputs.push((
+        "union_sparse",
+        vec![Arc::new(
+            UnionArray::try_new(
+                union_fields.clone(),
+                ScalarBuffer::from(vec![0i8, 5, 0]),
+                None,
+                vec![
+                    Arc::new(Int32Array::from(vec![Some(1), None, None])),
+                    Arc::new(StringArray::from(vec![None, Some("a"), None])),
+                ],
+            )
+            .unwrap(),
+        )],
+    ));
+    inputs.push((
+        "union_dense",
+        vec![Arc::new(
+            UnionArray::try_new(
+                union_fields,
+                ScalarBuffer::from(vec![0i8, 5, 0]),
+                Some(ScalarBuffer::from(vec![0i32, 0, 1])),
+                vec![
+                    Arc::new(Int32Array::from(vec![Some(1), None])),
+                    Arc::new(StringArray::from(vec![Some("a")])),
+                ],
+            )
+            .unwrap(),
+        )],
+    ));
+
+    // Encodings
+    inputs.push((
+        "dictionary",
+        vec![Arc::new(
+            vec![Some("a"), None, Some("b"), Some("a")]
+                .into_iter()
+                .collect::<DictionaryArray<Int8Type>>(),
+        )],
+    ));
+    inputs.push((
+        "run_end_encoded",
+        vec![Arc::new(
+            RunArray::<Int32Type>::try_new(
+                &Int32Array::from(vec![2, 3, 6]),
+                &StringArray::from(vec![Some("a"), None, Some("b")]),
+            )
+            .unwrap(),
+        )],
+    ));
+
+    // Slices are taken after decoding, so offsets of all nested arrays are non-zero
+    let find_input = |name: &str| inputs.iter().find(|(n, _)| *n == name).unwrap().1[0].clone();
+    let sliced: Vec<(&str, ArrayRef, Slice)> = vec![
+        (
+            "int32_sliced",
+            Arc::new(Int32Array::from(vec![Some(1), None, Some(3), Some(4)])),
+            Slice {
+                offset: 1,
+                length: 2,
+            },
+        ),
+        (
+            "bool_sliced",
+            Arc::new(BooleanArray::from(
+                (0..20)
+                    .map(|i| (i % 3 != 0).then_some(i % 2 == 0))
+                    .collect::<Vec<_>>(),
+            )),
+            Slice {
+                offset: 3,
+                length: 13,
+            },
+        ),
+        (
+            "utf8_sliced",
+            Arc::new(StringArray::from(vec![
+                Some("a"),
+                None,
+                Some("bc"),
+                Some(""),
+                None,
+            ])),
+            Slice {
+                offset: 1,
+                length: 3,
+            },
+        ),
+        (
+            "list_sliced",
+            find_input("list"),
+            Slice {
+                offset: 1,
+                length: 2,
+            },
+        ),
+        (
+            "list_view_sliced",
+            find_input("list_view"),
+            Slice {
+                offset: 1,
+                length: 3,
+            },
+        ),
+        (
+            "struct_sliced",
+            find_input("struct_nested"),
+            Slice {
+                offset: 1,
+                length: 2,
+            },
+        ),
+        (
+            "map_sliced",
+            find_input("map"),
+            Slice {
+                offset: 1,
+                length: 2,
+            },
+        ),
+        (
+            "union_dense_sliced",
+            find_input("union_dense"),
+            Slice {
+                offset: 1,
+                length: 2,
+            },
+        ),
+        (
+            "run_end_encoded_sliced",
+            find_input("run_end_encoded"),
+            Slice {
+                offset: 1,
+                length: 4,
+            },
+        ),
+    ];
+
+    inputs
+        .into_iter()
+        .map(|(name, arrays)| (name.to_string(), column_batches(arrays), None))
+        .chain(sliced.into_iter().map(|(name, array, slice)| {
+            (name.to_string(), column_batches(vec![array]), Some(slice))
+        }))
+        .collect()
+}
+
+fn record_inputs() -> Vec<(String, Vec<RecordBatch>, Option<Slice>)> {
+    let mut inputs = Vec::new();
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("a", DataType::Int32, false),
+        Field::new("b", DataType::Utf8, true),
+    ]));
+    let batch = RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(Int32Array::from(vec![1, 2, 3])),
+            Arc::new(StringArray::from(vec![Some("x"), None, Some("z")])),
+        ],
+    )
+    .unwrap();
+    inputs.push(("batch_mixed".to_string(), vec![batch.clone()], None));
+    inputs.push((
+        "batch_mixed_split".to_string(),
+        vec![batch.slice(0, 1), batch.slice(1, 2)],
+        None,
+    ));
+
+    let struct_fields = Fields::from(vec![
+        Field::new("c", DataType::Utf8, true),
+        Field::new("d", DataType::Int32, true),
+    ]);
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("a", DataType::Int32, false),
+        Field::new("b", DataType::Struct(struct_fields.clone()), true),
+    ]));
+    let batch = RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(Int32Array::from(vec![1, 2, 3])),
+            Arc::new(StructArray::new(
+                struct_fields,
+                vec![
+                    Arc::new(StringArray::from(vec![Some("x"), None, Some("z")])),
+                    Arc::new(Int32Array::from(vec![Some(1), Some(2), None])),
+                ],
+                Some(NullBuffer::from(vec![true, false, true])),
+            )),
+        ],
+    )
+    .unwrap();
+    inputs.push(("batch_nested".to_string(), vec![batch.clone()], None));
+    inputs.push((
+        "batch_nested_split".to_string(),
+        vec![batch.slice(0, 2), batch.slice(2, 1)],
+        None,
+    ));
+    inputs.push((
+        "batch_nested_sliced".to_string(),
+        vec![batch],
+        Some(Slice {
+            offset: 1,
+            length: 2,
+        }),
+    ));
+
+    inputs
+}